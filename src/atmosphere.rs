@@ -0,0 +1,208 @@
+//! Rayleigh and Mie scattering in a thin shell around a body, ray-marched
+//! per fragment.
+//!
+//! The shell is drawn as a sphere `1 + height` times the body's size,
+//! blended over whatever is behind it with
+//! [`BlendMode::Premultiplied`](crate::framebuffer::BlendMode::Premultiplied):
+//! each fragment carries the light scattered towards the eye along its view
+//! ray, and an alpha of one minus what the ray lets through.
+
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::shaders::FragmentShader;
+use crate::Uniforms;
+use nalgebra_glm::{Vec3, Vec4};
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+/// Samples along each view ray.
+const VIEW_SAMPLES: usize = 12;
+
+/// Samples along each ray from a view sample towards the sun.
+const LIGHT_SAMPLES: usize = 4;
+
+/// Lengths are in body radii, so a coefficient of 1 scatters or absorbs
+/// `1 - 1/e` of the light over one radius of air at ground density.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Atmosphere {
+    /// Thickness of the shell.
+    pub height: f32,
+    /// Rayleigh scattering coefficients for red, green and blue. Blue
+    /// scattering more is what makes limbs blue and sunsets red.
+    pub rayleigh: [f32; 3],
+    /// Height over which Rayleigh density falls by `1/e`.
+    pub rayleigh_scale_height: f32,
+    /// Mie (haze and dust) scattering per channel; usually grey.
+    pub mie: [f32; 3],
+    /// Light the haze absorbs rather than scatters, per channel. Absorbing
+    /// blue is what tints a thick haze yellow.
+    pub mie_absorption: [f32; 3],
+    pub mie_scale_height: f32,
+    /// How strongly Mie scattering favors the forward direction, in
+    /// `(-1, 1)`.
+    pub mie_anisotropy: f32,
+    /// Brightness of the sunlight entering the atmosphere.
+    pub sun_intensity: f32,
+}
+
+impl Default for Atmosphere {
+    /// Earth's air, with the shell and scale heights thickened so it shows
+    /// at planetarium scale while the column densities stay Earth-like.
+    fn default() -> Self {
+        Atmosphere {
+            height: 0.1,
+            rayleigh: [1.9, 4.4, 10.6],
+            rayleigh_scale_height: 0.025,
+            mie: [3.0, 3.0, 3.0],
+            mie_absorption: [0.3, 0.3, 0.3],
+            mie_scale_height: 0.008,
+            mie_anisotropy: 0.76,
+            sun_intensity: 12.0,
+        }
+    }
+}
+
+impl Atmosphere {
+    /// Why these parameters can't be drawn, if they can't.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.height.is_finite() && self.height > 0.0) {
+            return Err(format!("atmosphere height must be positive, got {}", self.height));
+        }
+        if !(self.rayleigh_scale_height > 0.0 && self.mie_scale_height > 0.0) {
+            return Err("atmosphere scale heights must be positive".to_string());
+        }
+        let coefficients = self.rayleigh.iter().chain(&self.mie).chain(&self.mie_absorption);
+        if !coefficients.chain([&self.sun_intensity]).all(|c| c.is_finite() && *c >= 0.0) {
+            return Err("atmosphere coefficients must not be negative".to_string());
+        }
+        if !(self.mie_anisotropy.is_finite() && self.mie_anisotropy.abs() < 1.0) {
+            return Err(format!(
+                "mie_anisotropy must be between -1 and 1, got {}",
+                self.mie_anisotropy
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scattered light and view transmittance along a ray from `origin` in
+    /// direction `direction` (unit length), for a body of radius 1 at the
+    /// origin lit from `sun_direction`.
+    pub fn scatter(&self, origin: Vec3, direction: Vec3, sun_direction: Vec3) -> (Vec3, Vec3) {
+        let top = 1.0 + self.height;
+        let no_effect = (Vec3::zeros(), Vec3::repeat(1.0));
+        let Some((near, far)) = sphere_hits(origin, direction, top) else {
+            return no_effect;
+        };
+        let start = near.max(0.0);
+        // Stop at the ground if the ray reaches it.
+        let end = match sphere_hits(origin, direction, 1.0) {
+            Some((ground, _)) if ground > 0.0 => ground.min(far),
+            _ => far,
+        };
+        if end <= start {
+            return no_effect;
+        }
+
+        let rayleigh = Vec3::from(self.rayleigh);
+        let mie = Vec3::from(self.mie);
+        let haze = mie + Vec3::from(self.mie_absorption);
+        let extinction = |depth: (f32, f32)| rayleigh * depth.0 + haze * depth.1;
+
+        let step = (end - start) / VIEW_SAMPLES as f32;
+        let mut view_depth = (0.0, 0.0);
+        let mut rayleigh_sum = Vec3::zeros();
+        let mut mie_sum = Vec3::zeros();
+        for i in 0..VIEW_SAMPLES {
+            let point = origin + direction * (start + step * (i as f32 + 0.5));
+            let (rayleigh_density, mie_density) = self.density(point);
+            view_depth.0 += rayleigh_density * step;
+            view_depth.1 += mie_density * step;
+
+            let Some(light_depth) = self.light_depth(point, sun_direction) else {
+                continue;
+            };
+            let total = extinction((view_depth.0 + light_depth.0, view_depth.1 + light_depth.1));
+            let attenuation = total.map(|x| (-x).exp());
+            rayleigh_sum += attenuation * (rayleigh_density * step);
+            mie_sum += attenuation * (mie_density * step);
+        }
+
+        let cosine = direction.dot(&sun_direction);
+        let scattered = (rayleigh_sum.component_mul(&rayleigh) * rayleigh_phase(cosine)
+            + mie_sum.component_mul(&mie) * mie_phase(cosine, self.mie_anisotropy))
+            * self.sun_intensity;
+        (scattered, extinction(view_depth).map(|x| (-x).exp()))
+    }
+
+    /// Rayleigh and Mie densities at `point`, relative to the ground.
+    fn density(&self, point: Vec3) -> (f32, f32) {
+        let altitude = (point.magnitude() - 1.0).max(0.0);
+        (
+            (-altitude / self.rayleigh_scale_height).exp(),
+            (-altitude / self.mie_scale_height).exp(),
+        )
+    }
+
+    /// Rayleigh and Mie optical depths from `point` to the top of the
+    /// atmosphere towards the sun, or `None` if the body is in the way.
+    fn light_depth(&self, point: Vec3, sun_direction: Vec3) -> Option<(f32, f32)> {
+        if let Some((ground, _)) = sphere_hits(point, sun_direction, 1.0) {
+            if ground > 0.0 {
+                return None;
+            }
+        }
+        let (_, exit) = sphere_hits(point, sun_direction, 1.0 + self.height)?;
+        let step = exit.max(0.0) / LIGHT_SAMPLES as f32;
+        let mut depth = (0.0, 0.0);
+        for i in 0..LIGHT_SAMPLES {
+            let (rayleigh, mie) = self.density(point + sun_direction * (step * (i as f32 + 0.5)));
+            depth.0 += rayleigh * step;
+            depth.1 += mie * step;
+        }
+        Some(depth)
+    }
+}
+
+impl FragmentShader for Atmosphere {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        // The shell is drawn scaled by `1 + height` about the body's
+        // center; work in units of the body's radius from that center.
+        let model = &uniforms.model_matrix;
+        let center = (model * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+        let radius = (model * Vec4::new(1.0, 0.0, 0.0, 0.0)).xyz().magnitude() / (1.0 + self.height);
+        let to_body = |point: Vec3| (point - center) / radius;
+
+        let eye = to_body(uniforms.camera_position);
+        let direction = (to_body(fragment.world_position) - eye).normalize();
+        let sun_direction = (sun_position - center).normalize();
+        let (scattered, transmittance) = self.scatter(eye, direction, sun_direction);
+
+        let coverage = 1.0 - (transmittance.x + transmittance.y + transmittance.z) / 3.0;
+        Color::from_rgba(scattered.x, scattered.y, scattered.z, coverage)
+    }
+}
+
+/// Distances along the ray to where it enters and leaves a sphere of
+/// `radius` at the origin, if it meets it at all. Either may be negative.
+fn sphere_hits(origin: Vec3, direction: Vec3, radius: f32) -> Option<(f32, f32)> {
+    let b = origin.dot(&direction);
+    let c = origin.magnitude_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
+
+fn rayleigh_phase(cosine: f32) -> f32 {
+    3.0 / (16.0 * PI) * (1.0 + cosine * cosine)
+}
+
+/// Cornette-Shanks phase function.
+fn mie_phase(cosine: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    3.0 / (8.0 * PI) * ((1.0 - g2) * (1.0 + cosine * cosine))
+        / ((2.0 + g2) * (1.0 + g2 - 2.0 * g * cosine).powf(1.5))
+}
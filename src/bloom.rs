@@ -0,0 +1,187 @@
+//! Glow around the brightest parts of a frame, added to the framebuffer's
+//! HDR colors before they are tone-mapped.
+//!
+//! Pixels brighter than a threshold are copied out, halved in size a few
+//! times, blurred at every size and added back, so small wide halos and
+//! tight bright cores come from the same pass.
+
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+/// Weights of the separable blur applied at each scale, from the center tap
+/// out; a Gaussian with a standard deviation of about 1.5 pixels.
+const BLUR_WEIGHTS: [f32; 5] = [0.2660, 0.2130, 0.1093, 0.0360, 0.0076];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bloom {
+    /// Luminance above which a pixel glows. Only the excess spreads, so a
+    /// pixel just over the threshold barely glows at all.
+    pub threshold: f32,
+    /// How much of the blurred light is added back; 0 turns bloom off.
+    pub intensity: f32,
+    /// Number of scales, each half the size of the one before.
+    pub levels: usize,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 3.0,
+            intensity: 1.0,
+            levels: 5,
+        }
+    }
+}
+
+impl Bloom {
+    /// Adds the glow to `framebuffer.color`.
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.intensity <= 0.0 || self.levels == 0 {
+            return;
+        }
+        let bright = Layer {
+            width: framebuffer.width,
+            height: framebuffer.height,
+            pixels: framebuffer
+                .color
+                .iter()
+                .map(|color| self.excess(*color))
+                .collect(),
+        };
+
+        let mut scales = Vec::with_capacity(self.levels);
+        let mut layer = bright;
+        for _ in 0..self.levels {
+            if layer.width < 2 || layer.height < 2 {
+                break;
+            }
+            layer = layer.downsample();
+            scales.push(layer.blur());
+        }
+        let Some(mut glow) = scales.pop() else {
+            return;
+        };
+        // Coarsest first, each scale adding its detail to the one above.
+        while let Some(finer) = scales.pop() {
+            glow = finer.add(&glow.upsample(finer.width, finer.height));
+        }
+        let glow = glow.upsample(framebuffer.width, framebuffer.height);
+
+        let weight = self.intensity / self.levels as f32;
+        for (pixel, light) in framebuffer.color.iter_mut().zip(&glow.pixels) {
+            *pixel = *pixel + *light * weight;
+        }
+    }
+
+    /// The part of `color` brighter than the threshold, keeping its hue.
+    fn excess(&self, color: Color) -> Color {
+        let luminance = color.luminance();
+        if luminance <= self.threshold {
+            return Color::black();
+        }
+        color * ((luminance - self.threshold) / luminance)
+    }
+}
+
+/// An image at one scale of the bloom.
+struct Layer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Layer {
+    fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Half the size, each pixel averaging a 2x2 block. A trailing odd row or
+    /// column is folded into the last pixel, which then averages up to 3x3.
+    fn downsample(&self) -> Layer {
+        let (width, height) = (self.width / 2, self.height / 2);
+        // Source rows or columns covered by output pixel `i` of `size`.
+        let span = |i: usize, size: usize, source: usize| {
+            i * 2..if i + 1 == size { source } else { i * 2 + 2 }
+        };
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let rows = span(y, height, self.height);
+            for x in 0..width {
+                let columns = span(x, width, self.width);
+                let mut sum = Color::black();
+                for sy in rows.clone() {
+                    for sx in columns.clone() {
+                        sum = sum + self.get(sx, sy);
+                    }
+                }
+                pixels.push(sum * (1.0 / (rows.len() * columns.len()) as f32));
+            }
+        }
+        Layer { width, height, pixels }
+    }
+
+    /// Blurred horizontally, then vertically, clamping at the edges.
+    fn blur(&self) -> Layer {
+        let horizontal = self.blur_pass(1, 0);
+        horizontal.blur_pass(0, 1)
+    }
+
+    fn blur_pass(&self, dx: isize, dy: isize) -> Layer {
+        let clamp = |value: isize, size: usize| value.clamp(0, size as isize - 1) as usize;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut sum = self.get(x, y) * BLUR_WEIGHTS[0];
+                for (offset, weight) in BLUR_WEIGHTS.iter().enumerate().skip(1) {
+                    let offset = offset as isize;
+                    for sign in [-1, 1] {
+                        let sx = clamp(x as isize + dx * offset * sign, self.width);
+                        let sy = clamp(y as isize + dy * offset * sign, self.height);
+                        sum = sum + self.get(sx, sy) * *weight;
+                    }
+                }
+                pixels.push(sum);
+            }
+        }
+        Layer {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    /// Bilinearly resampled to `width` by `height`.
+    fn upsample(&self, width: usize, height: usize) -> Layer {
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let fy = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (self.height - 1) as f32);
+            let (y0, ty) = (fy as usize, fy.fract());
+            let y1 = (y0 + 1).min(self.height - 1);
+            for x in 0..width {
+                let fx = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (self.width - 1) as f32);
+                let (x0, tx) = (fx as usize, fx.fract());
+                let x1 = (x0 + 1).min(self.width - 1);
+                let top = self.get(x0, y0).lerp(&self.get(x1, y0), tx);
+                let bottom = self.get(x0, y1).lerp(&self.get(x1, y1), tx);
+                pixels.push(top.lerp(&bottom, ty));
+            }
+        }
+        Layer { width, height, pixels }
+    }
+
+    fn add(&self, other: &Layer) -> Layer {
+        let pixels = self
+            .pixels
+            .iter()
+            .zip(&other.pixels)
+            .map(|(a, b)| *a + *b)
+            .collect();
+        Layer {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::vertex::Vertex;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+/// Signed distance of a clip-space position to each of the six frustum
+/// planes (`-w <= x, y, z <= w`); a vertex is inside where all are `>= 0`.
+fn plane_distances(p: &Vec4) -> [f32; 6] {
+    [
+        p.w + p.x,
+        p.w - p.x,
+        p.w + p.y,
+        p.w - p.y,
+        p.w + p.z,
+        p.w - p.z,
+    ]
+}
+
+/// Clips a triangle against the view frustum in homogeneous clip space
+/// using Sutherland–Hodgman, one plane at a time.
+///
+/// Returns the clipped polygon as a convex fan (empty if the triangle is
+/// entirely outside). Triangles fully inside are returned unchanged.
+pub fn clip_triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Vertex> {
+    let distances = [
+        plane_distances(&v1.clip_position),
+        plane_distances(&v2.clip_position),
+        plane_distances(&v3.clip_position),
+    ];
+
+    let mut inside_all = true;
+    for plane in 0..6 {
+        let outside = distances.iter().filter(|d| d[plane] < 0.0).count();
+        if outside == 3 {
+            return Vec::new();
+        }
+        inside_all &= outside == 0;
+    }
+    if inside_all {
+        return vec![v1.clone(), v2.clone(), v3.clone()];
+    }
+
+    let mut polygon = vec![v1.clone(), v2.clone(), v3.clone()];
+    for plane in 0..6 {
+        polygon = clip_polygon_against_plane(&polygon, plane);
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+    }
+    polygon
+}
+
+fn clip_polygon_against_plane(polygon: &[Vertex], plane: usize) -> Vec<Vertex> {
+    let mut output = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let d_current = plane_distances(&current.clip_position)[plane];
+        let d_next = plane_distances(&next.clip_position)[plane];
+
+        if d_current >= 0.0 {
+            output.push(current.clone());
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            output.push(current.lerp(next, t));
+        }
+    }
+
+    output
+}
+
+/// Performs the perspective divide and viewport transform, filling in
+/// `transformed_position` from `clip_position`.
+pub fn project_to_screen(vertex: &mut Vertex, viewport_matrix: &Mat4) {
+    let clip = vertex.clip_position;
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = viewport_matrix * ndc;
+    vertex.transformed_position = Vec3::new(screen.x, screen.y, screen.z);
+}
+
+/// The six planes of a view frustum in world space, for rejecting whole
+/// objects before their vertices are transformed.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    /// `(normal, offset)` per plane, in the order of [`plane_distances`],
+    /// with unit normals pointing inwards.
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from a combined projection and view matrix.
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.xyz().magnitude();
+            if length > 0.0 {
+                plane / length
+            } else {
+                plane
+            }
+        });
+        Frustum { planes }
+    }
+
+    /// Whether any part of the sphere may be inside. Spheres near a corner
+    /// can pass while lying just outside, but a sphere that is rejected is
+    /// never visible.
+    pub fn intersects_sphere(&self, center: &Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(center) + plane.w >= -radius)
+    }
+}
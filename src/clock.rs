@@ -0,0 +1,96 @@
+/// Simulation time in seconds, advanced in fixed steps independent of how
+/// fast frames are rendered.
+///
+/// Real elapsed time is scaled by the time scale (negative runs the
+/// simulation backwards) and accumulated; every whole `fixed_step` of
+/// simulated time becomes one update step.
+pub struct SimulationClock {
+    time: f64,
+    fixed_step: f64,
+    accumulator: f64,
+    time_scale: f64,
+    paused: bool,
+    max_steps: u32,
+}
+
+impl SimulationClock {
+    pub fn new(fixed_step: f64) -> Self {
+        SimulationClock {
+            time: 0.0,
+            fixed_step,
+            accumulator: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            max_steps: 4096,
+        }
+    }
+
+    /// Current simulation time in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn fixed_step(&self) -> f64 {
+        self.fixed_step
+    }
+
+    /// Signed duration of each update step: `fixed_step`, negated while
+    /// running in reverse.
+    pub fn step(&self) -> f64 {
+        self.fixed_step.copysign(self.time_scale)
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        // Leftover time from the old direction would otherwise be replayed
+        // in the new one.
+        if time_scale.signum() != self.time_scale.signum() {
+            self.accumulator = 0.0;
+        }
+        self.time_scale = time_scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Caps the number of steps a single `advance` may produce. Time beyond
+    /// the cap is dropped so a slow frame cannot snowball into ever longer
+    /// ones.
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+
+    /// Accounts for `real_dt` seconds of wall-clock time and returns how many
+    /// update steps of [`step`](Self::step) seconds are now due. The clock's
+    /// time has already moved past those steps when this returns.
+    pub fn advance(&mut self, real_dt: f64) -> u32 {
+        if self.paused || self.fixed_step <= 0.0 {
+            return 0;
+        }
+
+        self.accumulator += (real_dt * self.time_scale).abs();
+        let due = (self.accumulator / self.fixed_step).floor();
+        let steps = due.min(self.max_steps as f64) as u32;
+
+        if due > self.max_steps as f64 {
+            self.accumulator = 0.0;
+        } else {
+            self.accumulator -= steps as f64 * self.fixed_step;
+        }
+        self.time += steps as f64 * self.step();
+
+        steps
+    }
+}
@@ -1,59 +1,106 @@
 use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+/// Linear RGB with float channels, plus an opacity. `1.0` is nominal full
+/// brightness, but nothing clamps: brighter values survive until the
+/// framebuffer tone-maps them for display.
+///
+/// Colors written the way they look on screen (8-bit values, hex codes and
+/// shader palettes) are sRGB-encoded and come in through [`Color::new`],
+/// [`Color::from_hex`] or [`Color::from_srgb`]; [`Color::to_hex`] encodes
+/// back for display.
+///
+/// Color arithmetic (`+`, `*` and the `blend_*` mixes) acts on `r`, `g` and
+/// `b` and keeps the left-hand color's alpha; `blend_normal` picks one of
+/// the two colors whole.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    /// Opacity from 0 (invisible) to 1 (opaque). Only blended draws use it;
+    /// see [`BlendMode`](crate::framebuffer::BlendMode).
+    pub a: f32,
 }
 
 impl Color {
+    /// From 8-bit sRGB channels, where 255 maps to `1.0`.
     pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        Color::from_srgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
     }
 
+    /// An opaque color from sRGB-encoded channels, decoded to linear. Values
+    /// outside `[0, 1]` follow the curve's extension rather than clipping.
+    pub fn from_srgb(r: f32, g: f32, b: f32) -> Self {
+        Color::from_float(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+    }
+
+    /// Clamps every channel, alpha included, to `[0, 1]`.
     pub fn clamp(self) -> Self {
         Self {
-            r: self.r.clamp(0, 255),
-            g: self.g.clamp(0, 255),
-            b: self.b.clamp(0, 255),
+            r: self.r.clamp(0.0, 1.0),
+            g: self.g.clamp(0.0, 1.0),
+            b: self.b.clamp(0.0, 1.0),
+            a: self.a.clamp(0.0, 1.0),
         }
     }
 
     pub fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color::from_float(0.0, 0.0, 0.0)
     }
 
+    /// An opaque color.
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
-        Color {
-            r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-            g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-            b: (b.clamp(0.0, 1.0) * 255.0) as u8,
-        }
+        Color::from_rgba(r, g, b, 1.0)
+    }
+
+    /// A straight (not premultiplied) color with opacity `a`.
+    pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Color { a, ..self }
+    }
+
+    /// The color channels scaled by alpha, as
+    /// [`BlendMode::Premultiplied`](crate::framebuffer::BlendMode::Premultiplied)
+    /// expects.
+    pub fn premultiplied(self) -> Self {
+        Color::from_rgba(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
     }
 
     pub fn from_hex(hex: u32) -> Self {
         let r = ((hex >> 16) & 0xFF) as u8;
         let g = ((hex >> 8) & 0xFF) as u8;
         let b = (hex & 0xFF) as u8;
-        Color { r, g, b }
+        Color::new(r, g, b)
     }
 
+    /// sRGB-encoded `0xRRGGBB`, clamping channels outside `[0, 1]` and
+    /// dropping alpha.
     pub fn to_hex(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+        let Color { r, g, b, .. } = self.clamp();
+        let channel = |value: f32| (linear_to_srgb(value) * 255.0).round() as u32;
+        (channel(r) << 16) | (channel(g) << 8) | channel(b)
     }
 
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
         Color {
-            r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-            g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-            b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
         }
     }
 
     pub fn is_black(&self) -> bool {
-        self.r == 0 && self.g == 0 && self.b == 0
+        self.r == 0.0 && self.g == 0.0 && self.b == 0.0
+    }
+
+    /// Rec. 709 luminance.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
     }
 
     pub fn blend_normal(&self, blend: &Color) -> Color {
@@ -65,34 +112,30 @@ impl Color {
     }
 
     pub fn blend_multiply(&self, blend: &Color) -> Color {
-        Color::new(
-            ((self.r as f32 * blend.r as f32) / 255.0) as u8,
-            ((self.g as f32 * blend.g as f32) / 255.0) as u8,
-            ((self.b as f32 * blend.b as f32) / 255.0) as u8,
-        )
+        Color::from_rgba(self.r * blend.r, self.g * blend.g, self.b * blend.b, self.a)
     }
 
     pub fn blend_add(&self, blend: &Color) -> Color {
-        Color::new(
-            (self.r as u16 + blend.r as u16).min(255) as u8,
-            (self.g as u16 + blend.g as u16).min(255) as u8,
-            (self.b as u16 + blend.b as u16).min(255) as u8,
-        )
+        *self + *blend
     }
 
     pub fn blend_subtract(&self, blend: &Color) -> Color {
-        let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-        let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-        let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
-
-        Color::new(r, g, b)
+        Color::from_rgba(
+            (self.r - blend.r).max(0.0),
+            (self.g - blend.g).max(0.0),
+            (self.b - blend.b).max(0.0),
+            self.a,
+        )
     }
 
+    /// Screen blend of the displayable part of both colors.
     pub fn blend_screen(&self, blend: &Color) -> Color {
-        Color::new(
-            255 - ((255 - self.r as u16) * (255 - blend.r as u16) / 255) as u8,
-            255 - ((255 - self.g as u16) * (255 - blend.g as u16) / 255) as u8,
-            255 - ((255 - self.b as u16) * (255 - blend.b as u16) / 255) as u8,
+        let (base, blend) = (self.clamp(), blend.clamp());
+        Color::from_rgba(
+            1.0 - (1.0 - base.r) * (1.0 - blend.r),
+            1.0 - (1.0 - base.g) * (1.0 - blend.g),
+            1.0 - (1.0 - base.b) * (1.0 - blend.b),
+            self.a,
         )
     }
 }
@@ -104,9 +147,10 @@ impl Add for Color {
 
     fn add(self, other: Color) -> Color {
         Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+            a: self.a,
         }
     }
 }
@@ -118,15 +162,36 @@ impl Mul<f32> for Color {
 
     fn mul(self, scalar: f32) -> Color {
         Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+            a: self.a,
         }
     }
 }
 
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+        write!(f, "Color(r: {}, g: {}, b: {}, a: {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+/// The sRGB transfer function, from encoded to linear.
+fn srgb_to_linear(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let linear = if magnitude <= 0.04045 {
+        magnitude / 12.92
+    } else {
+        ((magnitude + 0.055) / 1.055).powf(2.4)
+    };
+    linear.copysign(value)
+}
+
+/// The inverse of [`srgb_to_linear`], for values in `[0, 1]`.
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
     }
 }
@@ -5,30 +5,13 @@ pub struct Fragment {
     pub position: Vec2,
     pub color: Color,
     pub depth: f32,
+    /// World-space surface normal, normalized.
     pub normal: Vec3,
     pub intensity: f32,
+    /// World-space position, for lighting.
     pub world_position: Vec3,
+    /// Position in the mesh's own space, for procedural texturing that
+    /// should stay fixed to the surface as the body moves.
+    pub object_position: Vec3,
     pub tex_coords: Vec2,
 }
-
-impl Fragment {
-    pub fn new(
-        position: Vec2,
-        color: Color,
-        depth: f32,
-        normal: Vec3,
-        intensity: f32,
-        world_position: Vec3,
-        tex_coords: Vec2,
-    ) -> Self {
-        Fragment {
-            position,
-            color,
-            depth,
-            normal,
-            intensity,
-            world_position,
-            tex_coords,
-        }
-    }
-}
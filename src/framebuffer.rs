@@ -1,10 +1,80 @@
+use crate::color::Color;
+use crate::tone_map::ToneMapper;
+use image::{ImageError, RgbImage};
+use std::path::Path;
+use std::thread;
+
+/// How a new color is combined with the one already in the framebuffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrites the pixel, ignoring alpha.
+    #[default]
+    Replace,
+    /// Straight alpha: `source * a + destination * (1 - a)`.
+    Alpha,
+    /// `destination + source * a`, for glows that only ever brighten.
+    Additive,
+    /// For colors already multiplied by their alpha:
+    /// `source + destination * (1 - a)`.
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "replace" => Some(BlendMode::Replace),
+            "alpha" => Some(BlendMode::Alpha),
+            "additive" => Some(BlendMode::Additive),
+            "premultiplied" => Some(BlendMode::Premultiplied),
+            _ => None,
+        }
+    }
+
+    /// Whether drawing with this mode depends on what is already drawn, so
+    /// must happen back to front after opaque geometry.
+    pub fn is_translucent(&self) -> bool {
+        *self != BlendMode::Replace
+    }
+
+    /// `source` drawn over `destination`.
+    pub fn blend(&self, source: Color, destination: Color) -> Color {
+        let coverage = source.a.clamp(0.0, 1.0);
+        let over = |source: f32, destination: f32| source + destination * (1.0 - coverage);
+        match self {
+            BlendMode::Replace => source,
+            BlendMode::Alpha => Color::from_rgba(
+                over(source.r * coverage, destination.r),
+                over(source.g * coverage, destination.g),
+                over(source.b * coverage, destination.b),
+                over(coverage, destination.a),
+            ),
+            BlendMode::Additive => (source * coverage + destination).with_alpha(destination.a),
+            BlendMode::Premultiplied => Color::from_rgba(
+                over(source.r, destination.r),
+                over(source.g, destination.g),
+                over(source.b, destination.b),
+                over(coverage, destination.a),
+            ),
+        }
+    }
+}
+
+/// Rendering happens in linear HDR `color`; [`resolve`](Self::resolve)
+/// tone-maps it into the displayable `buffer`.
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    /// Displayable pixels as `0xRRGGBB`, as of the last `resolve`.
     pub buffer: Vec<u32>,
+    pub color: Vec<Color>,
     pub zbuffer: Vec<f32>,
     background_color: u32,
     current_color: u32,
+    render_threads: usize,
+    blend_mode: BlendMode,
+    depth_write: bool,
+    tone_mapper: ToneMapper,
+    exposure: f32,
 }
 
 impl Framebuffer {
@@ -13,13 +83,24 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            color: vec![Color::black(); width * height],
             zbuffer: vec![f32::INFINITY; width * height],
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            render_threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            blend_mode: BlendMode::Replace,
+            depth_write: true,
+            tone_mapper: ToneMapper::default(),
+            exposure: 1.0,
         }
     }
 
+    /// Fills both buffers with the background color and resets depth.
     pub fn clear(&mut self) {
+        let background = Color::from_hex(self.background_color);
+        for pixel in self.color.iter_mut() {
+            *pixel = background;
+        }
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
         }
@@ -28,12 +109,17 @@ impl Framebuffer {
         }
     }
 
+    /// Draws the current color at a pixel if it passes the depth test,
+    /// honoring the blend mode and depth-write setting.
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
             if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
+                let source = Color::from_hex(self.current_color);
+                self.color[index] = self.blend_mode.blend(source, self.color[index]);
+                if self.depth_write {
+                    self.zbuffer[index] = depth;
+                }
             }
         }
     }
@@ -45,4 +131,72 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
+
+    /// Number of threads `render` shades tiles on; defaults to the number of
+    /// available cores.
+    pub fn render_threads(&self) -> usize {
+        self.render_threads
+    }
+
+    /// Sets the number of render threads. `1` renders on the calling thread.
+    pub fn set_render_threads(&mut self, threads: usize) {
+        self.render_threads = threads.max(1);
+    }
+
+    /// How `point` and `render` combine new colors with the framebuffer's.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Whether drawn pixels update the depth buffer. Depth is always
+    /// tested; translucent layers usually turn writing off so they don't
+    /// hide each other.
+    pub fn depth_write(&self) -> bool {
+        self.depth_write
+    }
+
+    pub fn set_depth_write(&mut self, depth_write: bool) {
+        self.depth_write = depth_write;
+    }
+
+    pub fn tone_mapper(&self) -> ToneMapper {
+        self.tone_mapper
+    }
+
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.tone_mapper = tone_mapper;
+    }
+
+    /// Multiplier applied to every color before tone mapping; defaults to 1.
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure.max(0.0);
+    }
+
+    /// Tone-maps the linear HDR `color` buffer and writes it to `buffer`
+    /// sRGB-encoded, for display.
+    pub fn resolve(&mut self) {
+        for (pixel, color) in self.buffer.iter_mut().zip(&self.color) {
+            *pixel = self.tone_mapper.apply(*color, self.exposure).to_hex();
+        }
+    }
+
+    /// The displayable `buffer` as an image; call `resolve` first.
+    pub fn to_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let pixel = self.buffer[y as usize * self.width + x as usize];
+            image::Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+        })
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), ImageError> {
+        self.to_image().save_with_format(path, image::ImageFormat::Png)
+    }
 }
@@ -0,0 +1,29 @@
+//! Helpers for rendering frames to disk without a window.
+
+use std::ops::Range;
+
+/// Parses a `--frames` value: either a single frame (`"42"`) or a
+/// half-open range (`"10..20"`). Empty or reversed ranges are rejected.
+pub fn parse_frame_range(value: &str) -> Result<Range<u32>, String> {
+    let parse = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|_| format!("invalid frame number `{}`", s))
+    };
+
+    match value.split_once("..") {
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start >= end {
+                return Err(format!("empty frame range `{}`", value));
+            }
+            Ok(start..end)
+        }
+        None => {
+            let frame = parse(value)?;
+            let end = frame
+                .checked_add(1)
+                .ok_or_else(|| format!("frame number too large `{}`", value))?;
+            Ok(frame..end)
+        }
+    }
+}
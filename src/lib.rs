@@ -0,0 +1,58 @@
+//! Software rasterizer for procedurally shaded planets.
+//!
+//! The interactive viewer in `main.rs` is a thin layer over this crate; the
+//! same pipeline can be driven headless or embedded in other tools.
+
+pub mod atmosphere;
+pub mod bloom;
+pub mod camera;
+pub mod clipping;
+pub mod clock;
+pub mod color;
+pub mod fragment;
+pub mod framebuffer;
+pub mod headless;
+pub mod lod;
+pub mod mesh;
+pub mod nbody;
+pub mod noise;
+pub mod normal_map;
+pub mod obj;
+pub mod orbit;
+pub mod procedural;
+pub mod render;
+pub mod scene;
+pub mod scene_file;
+pub mod shader_registry;
+pub mod shaders;
+pub mod shadow;
+pub mod skybox;
+pub mod texture;
+pub mod tone_map;
+pub mod triangle;
+pub mod vertex;
+
+pub use atmosphere::Atmosphere;
+pub use bloom::Bloom;
+pub use camera::Camera;
+pub use clock::SimulationClock;
+pub use color::Color;
+pub use framebuffer::{BlendMode, Framebuffer};
+pub use lod::LodMeshes;
+pub use mesh::{annulus, icosphere, uv_sphere};
+pub use nbody::{Integrator, NBodySystem};
+pub use obj::Obj;
+pub use orbit::OrbitalElements;
+pub use procedural::{PlanetClass, PlanetParams, ProceduralPlanetShader};
+pub use render::{
+    create_model_matrix, create_noise, create_perspective_matrix, create_view_matrix,
+    create_viewport_matrix, render, RenderStats, Uniforms,
+};
+pub use scene::{CelestialBody, FrameStats, Ring, Scene, SimulationMode};
+pub use scene_file::{load_scene, SceneError};
+pub use shader_registry::ShaderRegistry;
+pub use shaders::FragmentShader;
+pub use shadow::{Occluder, Shadows};
+pub use tone_map::ToneMapper;
+pub use triangle::{CullMode, FrontFace, RasterState};
+pub use vertex::Vertex;
@@ -0,0 +1,126 @@
+//! Level of detail: several tessellations of one shape, picked per frame by
+//! how large the shape appears on screen.
+
+use crate::mesh::{icosphere, uv_sphere};
+use crate::render::Uniforms;
+use crate::vertex::Vertex;
+
+/// How far, as a fraction, a projected radius must pass a level's threshold
+/// before the level changes. Without it a body sitting on a threshold would
+/// pop between meshes every frame.
+pub const LOD_HYSTERESIS: f32 = 0.15;
+
+pub struct LodLevel {
+    pub vertices: Vec<Vertex>,
+    /// Smallest projected radius, in pixels, this level is drawn at.
+    pub min_radius: f32,
+}
+
+/// Meshes of one shape from coarsest to finest.
+pub struct LodMeshes {
+    levels: Vec<LodLevel>,
+    radius: f32,
+}
+
+impl LodMeshes {
+    /// Orders `levels` by `min_radius`. The coarsest level is also used
+    /// below its own threshold.
+    ///
+    /// # Panics
+    ///
+    /// If `levels` is empty.
+    pub fn new(mut levels: Vec<LodLevel>) -> Self {
+        assert!(!levels.is_empty(), "LodMeshes needs at least one level");
+        levels.sort_by(|a, b| a.min_radius.total_cmp(&b.min_radius));
+        let radius = levels
+            .iter()
+            .flat_map(|level| &level.vertices)
+            .map(|vertex| vertex.position.magnitude())
+            .fold(0.0, f32::max);
+        LodMeshes { levels, radius }
+    }
+
+    /// One mesh at every size.
+    pub fn single(vertices: Vec<Vertex>) -> Self {
+        LodMeshes::new(vec![LodLevel {
+            vertices,
+            min_radius: 0.0,
+        }])
+    }
+
+    /// UV spheres from 120 triangles for specks up to about 4,000 for
+    /// bodies filling the screen.
+    pub fn uv_sphere() -> Self {
+        let level = |stacks, slices, min_radius| LodLevel {
+            vertices: uv_sphere(stacks, slices),
+            min_radius,
+        };
+        LodMeshes::new(vec![
+            level(6, 12, 0.0),
+            level(10, 20, 10.0),
+            level(16, 32, 40.0),
+            level(32, 64, 160.0),
+        ])
+    }
+
+    /// Icospheres of up to `subdivisions` levels, dropping one level each
+    /// time the projected radius halves below 120 pixels, down to three
+    /// levels coarser.
+    pub fn icosphere(subdivisions: u32) -> Self {
+        let coarsest = subdivisions.saturating_sub(3);
+        let levels = (coarsest..=subdivisions)
+            .map(|level| LodLevel {
+                vertices: icosphere(level),
+                min_radius: if level == coarsest {
+                    0.0
+                } else {
+                    120.0 / (1 << (subdivisions - level)) as f32
+                },
+            })
+            .collect();
+        LodMeshes::new(levels)
+    }
+
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    /// Distance from the origin to the furthest vertex of any level.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn mesh(&self, level: usize) -> &[Vertex] {
+        &self.levels[level.min(self.levels.len() - 1)].vertices
+    }
+
+    /// The level to draw at a projected `radius` in pixels, given the level
+    /// drawn last frame.
+    pub fn select(&self, current: usize, radius: f32) -> usize {
+        let mut level = current.min(self.levels.len() - 1);
+        while level + 1 < self.levels.len()
+            && radius >= self.levels[level + 1].min_radius * (1.0 + LOD_HYSTERESIS)
+        {
+            level += 1;
+        }
+        while level > 0 && radius < self.levels[level].min_radius * (1.0 - LOD_HYSTERESIS) {
+            level -= 1;
+        }
+        level
+    }
+}
+
+/// Radius in pixels of a sphere of `radius` seen at `distance` from the
+/// camera, using the larger of the horizontal and vertical scales of the
+/// projection and viewport in `uniforms`.
+pub fn projected_radius(radius: f32, distance: f32, uniforms: &Uniforms) -> f32 {
+    let (projection, viewport) = (&uniforms.projection_matrix, &uniforms.viewport_matrix);
+    let scale = (projection[(0, 0)] * viewport[(0, 0)])
+        .abs()
+        .max((projection[(1, 1)] * viewport[(1, 1)]).abs());
+    if distance <= 0.0 {
+        f32::INFINITY
+    } else {
+        radius * scale / distance
+    }
+}
@@ -1,381 +1,351 @@
 use minifb::{Key, Window, WindowOptions};
-use nalgebra_glm::{look_at, perspective, Mat4, Vec3};
-use std::f32::consts::PI;
-
-mod camera;
-mod color;
-mod fragment;
-mod framebuffer;
-mod normal_map;
-mod obj;
-mod shaders;
-mod skybox;
-mod texture;
-mod triangle;
-mod vertex;
-
-use crate::shaders::{
-    earth_shader, gas_giant_fragment_shader, jupiter_shader, mars_shader, mercury_shader,
-    moon_shader, rocky_planet_fragment_shader, saturn_shader, star_fragment_shader, 
-    venus_shader, vertex_shader, ShaderType,
+use nalgebra_glm::Vec3;
+use rust_planets::headless::parse_frame_range;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{
+    create_perspective_matrix, load_scene, Bloom, Camera, CelestialBody, FrameStats, Framebuffer,
+    Integrator, LodMeshes, Scene, ShaderRegistry, SimulationClock, SimulationMode, ToneMapper,
+    Uniforms,
 };
-use camera::Camera;
-use fastnoise_lite::{FastNoiseLite, NoiseType};
-use framebuffer::Framebuffer;
-use obj::Obj;
-use triangle::triangle;
-use vertex::Vertex;
-
-pub struct Uniforms {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: FastNoiseLite,
-}
-
-fn create_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::Cellular));
-    noise.set_frequency(Some(0.1));
-    noise
-}
-
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
-
-    let rotation_matrix_x = Mat4::new(
-        1.0, 0.0, 0.0, 0.0, 
-        0.0, cos_x, -sin_x, 0.0, 
-        0.0, sin_x, cos_x, 0.0, 
-        0.0, 0.0, 0.0, 1.0,
-    );
-
-    let rotation_matrix_y = Mat4::new(
-        cos_y, 0.0, sin_y, 0.0, 
-        0.0, 1.0, 0.0, 0.0, 
-        -sin_y, 0.0, cos_y, 0.0, 
-        0.0, 0.0, 0.0, 1.0,
-    );
-
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0, 
-        sin_z, cos_z, 0.0, 0.0, 
-        0.0, 0.0, 1.0, 0.0, 
-        0.0, 0.0, 0.0, 1.0,
-    );
-
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
-
-    let scale_matrix = Mat4::new(
-        scale, 0.0, 0.0, 0.0, 
-        0.0, scale, 0.0, 0.0, 
-        0.0, 0.0, scale, 0.0, 
-        0.0, 0.0, 0.0, 1.0,
-    );
-
-    let translation_matrix = Mat4::new(
-        1.0,
-        0.0,
-        0.0,
-        translation.x, 
-        0.0,
-        1.0,
-        0.0,
-        translation.y, 
-        0.0,
-        0.0,
-        1.0,
-        translation.z, 
-        0.0,
-        0.0,
-        0.0,
-        1.0,
-    );
-
-    translation_matrix * rotation_matrix * scale_matrix
-}
-
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
-
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
-}
-
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0,
-        0.0,
-        0.0,
-        width / 2.0, 
-        0.0,
-        -height / 2.0,
-        0.0,
-        height / 2.0, 
-        0.0,
-        0.0,
-        1.0,
-        0.0, 
-        0.0,
-        0.0,
-        0.0,
-        1.0,
-    )
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Instant;
+use std::{env, process};
+
+/// Rate of the fixed simulation step, and of the frames written in headless
+/// mode.
+const FRAME_RATE: f64 = 60.0;
+
+/// Scene shown when no `--scene` is given, built in so the viewer runs from
+/// any directory.
+const DEFAULT_SCENE: &str = include_str!("../assets/scenes/solar_system.toml");
+
+/// Speeds the `,` and `.` keys step through.
+const TIME_SCALES: [f64; 4] = [1.0, 10.0, 100.0, 1000.0];
+
+struct Options {
+    headless: bool,
+    frames: Range<u32>,
+    output_dir: PathBuf,
+    /// `None` for the built-in solar system.
+    scene_path: Option<PathBuf>,
+    mode: SimulationMode,
+    time_scale: f64,
+    threads: Option<usize>,
+    tone_mapper: ToneMapper,
+    exposure: f32,
+    bloom: Bloom,
 }
 
-fn render(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_type: &ShaderType,
-    sun_position: Vec3,
-) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    let mut fragments = Vec::new();
-    for tri in transformed_vertices.chunks(3) {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = match shader_type {
-                ShaderType::Star => star_fragment_shader(&fragment, uniforms),
-                ShaderType::Mercury => mercury_shader(&fragment, uniforms, sun_position),
-                ShaderType::Venus => venus_shader(&fragment, uniforms, sun_position),
-                ShaderType::Earth => earth_shader(&fragment, uniforms, sun_position),
-                ShaderType::Mars => mars_shader(&fragment, uniforms, sun_position),
-                ShaderType::Jupiter => jupiter_shader(&fragment, uniforms, sun_position),
-                ShaderType::Saturn => saturn_shader(&fragment, uniforms, sun_position),
-                ShaderType::Moon => moon_shader(&fragment, uniforms, sun_position),
-                ShaderType::RockyPlanet => {
-                    rocky_planet_fragment_shader(&fragment, uniforms, sun_position)
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut options = Options {
+            headless: false,
+            frames: 1..2,
+            output_dir: PathBuf::from("frames"),
+            scene_path: None,
+            mode: SimulationMode::Rails,
+            time_scale: 1.0,
+            threads: None,
+            tone_mapper: ToneMapper::default(),
+            exposure: 1.0,
+            bloom: Bloom::default(),
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--frames" => {
+                    let value = args.next().ok_or("--frames expects a value")?;
+                    options.frames = parse_frame_range(&value)?;
                 }
-                ShaderType::GasGiant => {
-                    gas_giant_fragment_shader(&fragment, uniforms, sun_position)
+                "--output" => {
+                    let value = args.next().ok_or("--output expects a directory")?;
+                    options.output_dir = PathBuf::from(value);
                 }
-                ShaderType::Custom(shader_fn) => shader_fn(&fragment, uniforms),
-            };
-
-            framebuffer.set_current_color(shaded_color.to_hex());
-            framebuffer.point(x, y, fragment.depth);
+                "--scene" => {
+                    let value = args.next().ok_or("--scene expects a file")?;
+                    options.scene_path = Some(PathBuf::from(value));
+                }
+                "--nbody" => {
+                    let value = args.next().ok_or("--nbody expects an integrator")?;
+                    let integrator = Integrator::from_name(&value)
+                        .ok_or_else(|| format!("unknown integrator `{}`", value))?;
+                    options.mode = SimulationMode::NBody(integrator);
+                }
+                "--time-scale" => {
+                    let value = args.next().ok_or("--time-scale expects a number")?;
+                    options.time_scale = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|scale| scale.is_finite())
+                        .ok_or_else(|| format!("invalid time scale `{}`", value))?;
+                }
+                "--threads" => {
+                    let value = args.next().ok_or("--threads expects a count")?;
+                    let threads = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&threads| threads > 0)
+                        .ok_or_else(|| format!("invalid thread count `{}`", value))?;
+                    options.threads = Some(threads);
+                }
+                "--tone-map" => {
+                    let value = args.next().ok_or("--tone-map expects an operator")?;
+                    options.tone_mapper = ToneMapper::from_name(&value)
+                        .ok_or_else(|| format!("unknown tone mapper `{}`", value))?;
+                }
+                "--exposure" => {
+                    let value = args.next().ok_or("--exposure expects a number")?;
+                    options.exposure = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|exposure| exposure.is_finite() && *exposure > 0.0)
+                        .ok_or_else(|| format!("invalid exposure `{}`", value))?;
+                }
+                "--bloom-threshold" => {
+                    let value = args.next().ok_or("--bloom-threshold expects a number")?;
+                    options.bloom.threshold = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|threshold| threshold.is_finite() && *threshold >= 0.0)
+                        .ok_or_else(|| format!("invalid bloom threshold `{}`", value))?;
+                }
+                "--bloom-intensity" => {
+                    let value = args.next().ok_or("--bloom-intensity expects a number")?;
+                    options.bloom.intensity = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|intensity| intensity.is_finite() && *intensity >= 0.0)
+                        .ok_or_else(|| format!("invalid bloom intensity `{}`", value))?;
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
         }
+
+        Ok(options)
     }
 }
 
 fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            eprintln!("usage: rust-planets [--scene FILE] [--nbody leapfrog|verlet|rk4] [--time-scale X] [--threads N] [--tone-map clamp|reinhard|aces|exposure] [--exposure X] [--bloom-threshold X] [--bloom-intensity X] [--headless] [--frames N | START..END] [--output DIR]");
+            process::exit(2);
+        }
+    };
+
     let window_width = 800;
     let window_height = 600;
     let framebuffer_width = 800;
     let framebuffer_height = 600;
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Rust Graphics - Renderer Example",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-    .unwrap();
-
-    window.set_position(500, 500);
-    window.update();
-
     framebuffer.set_background_color(0x000010);
+    framebuffer.set_tone_mapper(options.tone_mapper);
+    framebuffer.set_exposure(options.exposure);
+    if let Some(threads) = options.threads {
+        framebuffer.set_render_threads(threads);
+    }
 
-    
-    let obj = Obj::load("assets/models/sphere.obj").expect("Failed to load sphere obj");
-    let vertex_arrays = obj.get_vertex_array();
-    
-    // Load ring model
-    let ring_obj = Obj::load("assets/models/ring.obj").expect("Failed to load ring obj");
-    let ring_vertex_arrays = ring_obj.get_vertex_array();
-
-    let ring = Ring {
-        obj: ring_obj,
-        vertex_arrays: ring_vertex_arrays,
-        scale: 1.0,  // Ring scale relative to planet
-        rotation: Vec3::new(0.4, 0.0, 0.0),  // Match Saturn's tilt
+    let shaders = ShaderRegistry::default();
+    let sphere = LodMeshes::uv_sphere();
+    let loaded = match &options.scene_path {
+        Some(path) => load_scene(path, sphere, &shaders),
+        None => parse_scene(DEFAULT_SCENE, sphere, &shaders),
+    };
+    let mut scene = match loaded {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
     };
 
-    let mut celestial_bodies = vec![
-        
-        CelestialBody {
-            name: "Sun".to_string(),
-            position: Vec3::new(0.0, 0.0, 0.0),
-            scale: 2.0,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Star,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Mercury".to_string(),
-            position: Vec3::new(3.0, 1.0, -1.5),
-            scale: 0.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Mercury,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Venus".to_string(),
-            position: Vec3::new(-4.5, -1.0, 1.0),
-            scale: 0.6,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Venus,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Earth".to_string(),
-            position: Vec3::new(6.0, 0.5, -2.0),
-            scale: 0.6,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Earth,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Mars".to_string(),
-            position: Vec3::new(-7.0, -0.5, 1.5),
-            scale: 0.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Mars,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Jupiter".to_string(),
-            position: Vec3::new(9.0, 1.5, -3.0),
-            scale: 1.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Jupiter,
-            visible: true,
-            ring: None,
-        },
-        
-        CelestialBody {
-            name: "Saturn".to_string(),
-            position: Vec3::new(-12.0, -1.5, 2.0),
-            scale: 2.0,     // Increased scale further
-            rotation: Vec3::new(0.4, 0.0, 0.0),  // More pronounced tilt
-            shader_type: ShaderType::Saturn,
-            visible: true,
-            ring: Some(ring),  // Add the ring to Saturn
-        },
-        
-        CelestialBody {
-            name: "Moon".to_string(),
-            position: Vec3::new(6.8, 0.7, -2.2), // Slightly offset from Earth
-            scale: 0.16,                         // Much smaller than Earth
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: ShaderType::Moon,
-            visible: true,
-            ring: None,
-        },
-    ];
-
-    
-    let obj = Obj::load("assets/models/sphere.obj").expect("Failed to load obj");
-    let vertex_arrays = obj.get_vertex_array();
-    let mut time = 0;
-
-    
-    let noise = create_noise();
-    let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-    let viewport_matrix =
-        create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
     let mut uniforms = Uniforms {
-        model_matrix: Mat4::identity(),
-        view_matrix: Mat4::identity(),
-        projection_matrix,
-        viewport_matrix,
-        time: 0,
-        noise,
+        projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
+        ..Uniforms::new(framebuffer_width as f32, framebuffer_height as f32)
     };
 
-    
     let mut camera = Camera::new(
         Vec3::new(0.0, 0.0, 20.0),
         Vec3::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0),
     );
 
+    if options.headless {
+        if let Err(err) = run_headless(
+            &options,
+            &mut framebuffer,
+            &mut uniforms,
+            &camera,
+            &mut scene,
+        ) {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let mut window = Window::new(
+        "Rust Graphics - Renderer Example",
+        window_width,
+        window_height,
+        WindowOptions::default(),
+    )
+    .unwrap();
+
+    window.set_position(500, 500);
+    window.update();
+
+    let mut clock = SimulationClock::new(1.0 / FRAME_RATE);
+    clock.set_time_scale(options.time_scale);
+    scene.set_mode(options.mode);
+
+    let mut frame: u64 = 0;
+    let mut last_frame = Instant::now();
+    let mut stats = FrameStats::default();
+
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
             break;
         }
 
-        time += 1;
-
-        handle_input(&window, &mut camera, &mut celestial_bodies, &uniforms);
-
-        framebuffer.clear();
-
-        
-        uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        uniforms.time = time;
-
-        let sun_position = Vec3::new(0.0, 0.0, 0.0);
-
-        for body in &celestial_bodies {
-            if body.visible {
-                uniforms.model_matrix = create_model_matrix(body.position, body.scale, body.rotation);
-                render(
-                    &mut framebuffer,
-                    &uniforms,
-                    &vertex_arrays,
-                    &body.shader_type,
-                    sun_position,
-                );
-
-                // Render ring if present
-                if let Some(ring) = &body.ring {
-                    uniforms.model_matrix = create_model_matrix(
-                        body.position,
-                        body.scale * ring.scale,
-                        ring.rotation,
-                    );
-                    render(
-                        &mut framebuffer,
-                        &uniforms,
-                        &ring.vertex_arrays,
-                        &body.shader_type,  // Use same shader as planet
-                        sun_position,
-                    );
-                }
-            }
+        frame += 1;
+        let now = Instant::now();
+        // Long stalls (dragging the window, a debugger) shouldn't fast-forward.
+        let frame_time = now.duration_since(last_frame).as_secs_f64().min(0.25);
+        last_frame = now;
+
+        handle_input(&window, &mut camera, &mut scene.bodies);
+        handle_time_controls(&window, &mut clock);
+        if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
+            scene.set_mode(next_mode(scene.mode()));
+        }
+
+        for _ in 0..clock.advance(frame_time) {
+            scene.step(clock.step());
+        }
+
+        if frame.is_multiple_of(30) {
+            window.set_title(&status_line(&scene, &clock, stats));
         }
 
+        uniforms.time = scene.time() as f32;
+        stats = scene.render(&mut framebuffer, &mut uniforms, &camera);
+        options.bloom.apply(&mut framebuffer);
+        framebuffer.resolve();
+
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
             .unwrap();
     }
 }
 
-fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &mut Vec<CelestialBody>, uniforms: &Uniforms) {
+/// Renders the requested frame range without opening a window, writing each
+/// frame to `<output>/frame_NNNNN.png`. Frame `n` shows the simulation at
+/// `n / FRAME_RATE` seconds (times the time scale).
+fn run_headless(
+    options: &Options,
+    framebuffer: &mut Framebuffer,
+    uniforms: &mut Uniforms,
+    camera: &Camera,
+    scene: &mut Scene,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(&options.output_dir)?;
+
+    let mut clock = SimulationClock::new(1.0 / FRAME_RATE);
+    clock.set_time_scale(options.time_scale);
+    clock.set_max_steps(u32::MAX);
+    scene.set_mode(options.mode);
+
+    for frame in 0..options.frames.end {
+        if frame > 0 {
+            for _ in 0..clock.advance(1.0 / FRAME_RATE) {
+                scene.step(clock.step());
+            }
+        }
+        if frame < options.frames.start {
+            continue;
+        }
+
+        uniforms.time = scene.time() as f32;
+        let stats = scene.render(framebuffer, uniforms, camera);
+        options.bloom.apply(framebuffer);
+        framebuffer.resolve();
+
+        let path = options.output_dir.join(format!("frame_{:05}.png", frame));
+        framebuffer.save_png(&path)?;
+        println!("wrote {} ({})", path.display(), status_line(scene, &clock, stats));
+    }
+
+    Ok(())
+}
+
+fn next_mode(mode: SimulationMode) -> SimulationMode {
+    match mode {
+        SimulationMode::Rails => SimulationMode::NBody(Integrator::Leapfrog),
+        SimulationMode::NBody(Integrator::Leapfrog) => {
+            SimulationMode::NBody(Integrator::VelocityVerlet)
+        }
+        SimulationMode::NBody(Integrator::VelocityVerlet) => SimulationMode::NBody(Integrator::Rk4),
+        SimulationMode::NBody(Integrator::Rk4) => SimulationMode::Rails,
+    }
+}
+
+fn status_line(scene: &Scene, clock: &SimulationClock, stats: FrameStats) -> String {
+    let time = format!(
+        "t = {:.1}s at {}x{} - {} triangles, {} bodies culled",
+        scene.time(),
+        clock.time_scale(),
+        if clock.is_paused() { " (paused)" } else { "" },
+        stats.triangles,
+        stats.culled
+    );
+    match (scene.mode(), scene.drift()) {
+        (SimulationMode::NBody(integrator), Some(drift)) => format!(
+            "N-body ({}) - {} - energy drift {:+.3e}, momentum drift {:.3e}",
+            integrator.name(),
+            time,
+            drift.relative_energy,
+            drift.momentum
+        ),
+        _ => format!("Rails - {}", time),
+    }
+}
+
+fn handle_time_controls(window: &Window, clock: &mut SimulationClock) {
+    let pressed = |key| window.is_key_pressed(key, minifb::KeyRepeat::No);
+
+    if pressed(Key::P) {
+        clock.toggle_pause();
+    }
+    if pressed(Key::R) {
+        clock.set_time_scale(-clock.time_scale());
+    }
+
+    let speed = clock.time_scale().abs();
+    let current = TIME_SCALES
+        .iter()
+        .position(|&scale| scale >= speed)
+        .unwrap_or(TIME_SCALES.len() - 1);
+    let new_speed = if pressed(Key::Period) {
+        TIME_SCALES[(current + 1).min(TIME_SCALES.len() - 1)]
+    } else if pressed(Key::Comma) {
+        TIME_SCALES[current.saturating_sub(1)]
+    } else {
+        speed
+    };
+    clock.set_time_scale(new_speed.copysign(clock.time_scale()));
+}
+
+fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &mut [CelestialBody]) {
     let movement_speed = 1.0;
     let rotation_speed = PI / 50.0;
     let zoom_speed = 0.1;
@@ -420,57 +390,20 @@ fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &mut Vec
         camera.zoom(-zoom_speed);
     }
 
-    if window.is_key_pressed(Key::Key1, minifb::KeyRepeat::No) {
-        celestial_bodies[0].visible = !celestial_bodies[0].visible;
-    }
-    if window.is_key_pressed(Key::Key2, minifb::KeyRepeat::No) {
-        celestial_bodies[1].visible = !celestial_bodies[1].visible;
-    }
-    if window.is_key_pressed(Key::Key3, minifb::KeyRepeat::No) {
-        celestial_bodies[2].visible = !celestial_bodies[2].visible;
-    }
-    if window.is_key_pressed(Key::Key4, minifb::KeyRepeat::No) {
-        celestial_bodies[3].visible = !celestial_bodies[3].visible;
-    }
-    if window.is_key_pressed(Key::Key5, minifb::KeyRepeat::No) {
-        celestial_bodies[4].visible = !celestial_bodies[4].visible;
-    }
-    if window.is_key_pressed(Key::Key6, minifb::KeyRepeat::No) {
-        celestial_bodies[5].visible = !celestial_bodies[5].visible;
-    }
-    if window.is_key_pressed(Key::Key7, minifb::KeyRepeat::No) {
-        celestial_bodies[6].visible = !celestial_bodies[6].visible;
-    }
-    if window.is_key_pressed(Key::Key8, minifb::KeyRepeat::No) {
-        celestial_bodies[7].visible = !celestial_bodies[7].visible; // Toggle Moon visibility
+    let toggle_keys = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+    ];
+    for (key, body) in toggle_keys.iter().zip(celestial_bodies.iter_mut()) {
+        if window.is_key_pressed(*key, minifb::KeyRepeat::No) {
+            body.visible = !body.visible;
+        }
     }
-
-    // Update Moon position to orbit around Earth
-    let earth_position = celestial_bodies[3].position;
-    let orbit_speed = 0.02;
-    let orbit_radius = 0.8;
-    let moon = &mut celestial_bodies[7];
-    
-    moon.position = Vec3::new(
-        earth_position.x + orbit_radius * (uniforms.time as f32 * orbit_speed).cos(),
-        earth_position.y + 0.2 * (uniforms.time as f32 * orbit_speed * 0.5).sin(),
-        earth_position.z + orbit_radius * (uniforms.time as f32 * orbit_speed).sin()
-    );
-}
-
-struct Ring {
-    obj: Obj,
-    vertex_arrays: Vec<Vertex>,
-    scale: f32,
-    rotation: Vec3,
-}
-
-struct CelestialBody {
-    name: String,
-    position: Vec3,
-    scale: f32,
-    rotation: Vec3,
-    shader_type: ShaderType,
-    visible: bool,
-    ring: Option<Ring>,  // New field for optional ring
 }
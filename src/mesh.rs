@@ -0,0 +1,179 @@
+//! Meshes generated in code, as flat triangle lists ready for
+//! [`render`](crate::render::render).
+
+use crate::vertex::Vertex;
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+/// Texture coordinates of a point on the unit sphere, laid out like
+/// `tests/models/sphere.obj`: `u` runs once around the equator and `v` from
+/// 0 at the north pole (+Y) to 1 at the south pole.
+pub fn sphere_uv(direction: Vec3) -> Vec2 {
+    Vec2::new(
+        0.5 + (-direction.z).atan2(direction.x) / TAU,
+        direction.y.clamp(-1.0, 1.0).acos() / PI,
+    )
+}
+
+/// A unit sphere made by splitting each face of an icosahedron into four,
+/// `subdivisions` times, for `20 * 4^subdivisions` triangles of nearly equal
+/// size. Triangles wind counter-clockwise seen from outside.
+pub fn icosphere(subdivisions: u32) -> Vec<Vertex> {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges are shared by two faces; cache midpoints so both use the
+        // same vertex.
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, positions: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push((positions[a] + positions[b]).normalize());
+                positions.len() - 1
+            })
+        };
+        let mut split = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut positions);
+            let bc = midpoint(b, c, &mut positions);
+            let ca = midpoint(c, a, &mut positions);
+            split.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = split;
+    }
+
+    let mut vertices = Vec::with_capacity(faces.len() * 3);
+    for face in faces {
+        let corners = face.map(|index| positions[index]);
+        let uvs = triangle_uvs(corners);
+        for (position, tex_coords) in corners.into_iter().zip(uvs) {
+            vertices.push(Vertex::new(position, position, tex_coords));
+        }
+    }
+    vertices
+}
+
+/// Sphere texture coordinates for one triangle, fixed up so it does not
+/// smear across the whole texture where `u` wraps from 1 back to 0.
+fn triangle_uvs(corners: [Vec3; 3]) -> [Vec2; 3] {
+    let mut uvs = corners.map(sphere_uv);
+
+    let (min_u, max_u) = uvs
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), uv| (lo.min(uv.x), hi.max(uv.x)));
+    if max_u - min_u > 0.5 {
+        for uv in &mut uvs {
+            if uv.x < 0.5 {
+                uv.x += 1.0;
+            }
+        }
+    }
+
+    // `u` is undefined at a pole; take it from the other two corners.
+    for i in 0..3 {
+        if corners[i].x.abs() < 1e-6 && corners[i].z.abs() < 1e-6 {
+            uvs[i].x = (uvs[(i + 1) % 3].x + uvs[(i + 2) % 3].x) / 2.0;
+        }
+    }
+    uvs
+}
+
+/// A unit sphere of `stacks` bands of latitude and `slices` of longitude,
+/// with [`sphere_uv`] texture coordinates. Triangles wind
+/// counter-clockwise seen from outside.
+pub fn uv_sphere(stacks: u32, slices: u32) -> Vec<Vertex> {
+    let stacks = stacks.max(2);
+    let slices = slices.max(3);
+    let point = |i: u32, j: u32| {
+        let uv = Vec2::new(j as f32 / slices as f32, i as f32 / stacks as f32);
+        let (theta, phi) = (uv.y * PI, (uv.x - 0.5) * TAU);
+        let position = Vec3::new(theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin());
+        Vertex::new(position, position, uv)
+    };
+
+    let mut vertices = Vec::with_capacity((stacks * slices * 6) as usize);
+    for i in 0..stacks {
+        for j in 0..slices {
+            let (a, b, c, d) = (point(i, j), point(i + 1, j), point(i + 1, j + 1), point(i, j + 1));
+            // `u` is undefined at a pole; centering it on the slice keeps
+            // the texture from twisting there.
+            let pole_u = (j as f32 + 0.5) / slices as f32;
+            if i == 0 {
+                let mut a = a;
+                a.tex_coords.x = pole_u;
+                vertices.extend([a, b, c]);
+            } else if i == stacks - 1 {
+                let mut b = b;
+                b.tex_coords.x = pole_u;
+                vertices.extend([a, b, d]);
+            } else {
+                vertices.extend([a.clone(), b, c.clone(), a, c, d]);
+            }
+        }
+    }
+    vertices
+}
+
+/// A flat ring in the XZ plane between `inner_radius` and `outer_radius`,
+/// split into `segments` around. `u` runs around the ring like a sphere's
+/// longitude and `v` from 0 at the inner edge to 1 at the outer edge.
+/// Normals point along +Y and triangles wind counter-clockwise seen from
+/// above; draw it double-sided.
+pub fn annulus(inner_radius: f32, outer_radius: f32, segments: u32) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let point = |radius: f32, v: f32, j: u32| {
+        let u = j as f32 / segments as f32;
+        let phi = (u - 0.5) * TAU;
+        let position = Vec3::new(radius * phi.cos(), 0.0, -radius * phi.sin());
+        Vertex::new(position, Vec3::y(), Vec2::new(u, v))
+    };
+
+    let mut vertices = Vec::with_capacity((segments * 6) as usize);
+    for j in 0..segments {
+        let a = point(inner_radius, 0.0, j);
+        let b = point(outer_radius, 1.0, j);
+        let c = point(outer_radius, 1.0, j + 1);
+        let d = point(inner_radius, 0.0, j + 1);
+        vertices.extend([a.clone(), b, c.clone(), a, c, d]);
+    }
+    vertices
+}
@@ -0,0 +1,265 @@
+use nalgebra_glm::DVec3;
+
+/// Numerical scheme used to advance an [`NBodySystem`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Integrator {
+    /// Drift-kick-drift leapfrog. Symplectic, one force evaluation per step.
+    Leapfrog,
+    /// Kick-drift-kick velocity Verlet. Symplectic, one force evaluation per
+    /// step: the closing kick's forces open the next step.
+    VelocityVerlet,
+    /// Classical fourth-order Runge-Kutta. Accurate per step but not
+    /// symplectic, so energy slowly drifts over long runs.
+    Rk4,
+}
+
+impl Integrator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::Leapfrog => "leapfrog",
+            Integrator::VelocityVerlet => "velocity-verlet",
+            Integrator::Rk4 => "rk4",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "leapfrog" => Some(Integrator::Leapfrog),
+            "velocity-verlet" | "verlet" => Some(Integrator::VelocityVerlet),
+            "rk4" => Some(Integrator::Rk4),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub mass: f64,
+    pub position: DVec3,
+    pub velocity: DVec3,
+}
+
+/// Point masses interacting through Newtonian gravity.
+///
+/// Massless particles are allowed; they are pulled by every massive particle
+/// but exert no force themselves.
+#[derive(Clone, Debug)]
+pub struct NBodySystem {
+    particles: Vec<Particle>,
+    gravitational_constant: f64,
+    /// Plummer softening length, avoiding singular forces in close encounters.
+    softening: f64,
+    /// Accelerations at the current positions, left by the last velocity
+    /// Verlet step. Anything that could change them clears it.
+    cached_accelerations: Option<Vec<DVec3>>,
+}
+
+impl NBodySystem {
+    pub fn new(particles: Vec<Particle>, gravitational_constant: f64) -> Self {
+        NBodySystem {
+            particles,
+            gravitational_constant,
+            softening: 0.0,
+            cached_accelerations: None,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Mutable access to the particles. Drops any cached forces, since the
+    /// caller may move or reweigh them.
+    pub fn particles_mut(&mut self) -> &mut Vec<Particle> {
+        self.cached_accelerations = None;
+        &mut self.particles
+    }
+
+    pub fn gravitational_constant(&self) -> f64 {
+        self.gravitational_constant
+    }
+
+    pub fn softening(&self) -> f64 {
+        self.softening
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+        self.cached_accelerations = None;
+    }
+
+    /// Advances the system by `dt` using the given integrator.
+    pub fn step(&mut self, dt: f64, integrator: Integrator) {
+        match integrator {
+            Integrator::Leapfrog => self.step_leapfrog(dt),
+            Integrator::VelocityVerlet => self.step_velocity_verlet(dt),
+            Integrator::Rk4 => self.step_rk4(dt),
+        }
+        // Only velocity Verlet leaves forces that match the new positions.
+        if integrator != Integrator::VelocityVerlet {
+            self.cached_accelerations = None;
+        }
+    }
+
+    fn step_leapfrog(&mut self, dt: f64) {
+        for particle in &mut self.particles {
+            particle.position += particle.velocity * (0.5 * dt);
+        }
+        let accelerations = self.accelerations(&self.positions());
+        for (particle, acceleration) in self.particles.iter_mut().zip(&accelerations) {
+            particle.velocity += acceleration * dt;
+            particle.position += particle.velocity * (0.5 * dt);
+        }
+    }
+
+    fn step_velocity_verlet(&mut self, dt: f64) {
+        let accelerations = match self.cached_accelerations.take() {
+            Some(accelerations) => accelerations,
+            None => self.accelerations(&self.positions()),
+        };
+        for (particle, acceleration) in self.particles.iter_mut().zip(&accelerations) {
+            particle.velocity += acceleration * (0.5 * dt);
+            particle.position += particle.velocity * dt;
+        }
+        let accelerations = self.accelerations(&self.positions());
+        for (particle, acceleration) in self.particles.iter_mut().zip(&accelerations) {
+            particle.velocity += acceleration * (0.5 * dt);
+        }
+        self.cached_accelerations = Some(accelerations);
+    }
+
+    fn step_rk4(&mut self, dt: f64) {
+        let x0 = self.positions();
+        let v0: Vec<DVec3> = self.particles.iter().map(|p| p.velocity).collect();
+
+        // Each stage evaluates (dx/dt, dv/dt) = (v, a(x)) at an offset state.
+        let offset = |base: &[DVec3], delta: &[DVec3], scale: f64| -> Vec<DVec3> {
+            base.iter().zip(delta).map(|(b, d)| b + d * scale).collect()
+        };
+
+        let k1_x = v0.clone();
+        let k1_v = self.accelerations(&x0);
+
+        let k2_x = offset(&v0, &k1_v, 0.5 * dt);
+        let k2_v = self.accelerations(&offset(&x0, &k1_x, 0.5 * dt));
+
+        let k3_x = offset(&v0, &k2_v, 0.5 * dt);
+        let k3_v = self.accelerations(&offset(&x0, &k2_x, 0.5 * dt));
+
+        let k4_x = offset(&v0, &k3_v, dt);
+        let k4_v = self.accelerations(&offset(&x0, &k3_x, dt));
+
+        for (i, particle) in self.particles.iter_mut().enumerate() {
+            particle.position += (k1_x[i] + 2.0 * k2_x[i] + 2.0 * k3_x[i] + k4_x[i]) * (dt / 6.0);
+            particle.velocity += (k1_v[i] + 2.0 * k2_v[i] + 2.0 * k3_v[i] + k4_v[i]) * (dt / 6.0);
+        }
+    }
+
+    fn positions(&self) -> Vec<DVec3> {
+        self.particles.iter().map(|p| p.position).collect()
+    }
+
+    /// Gravitational acceleration of every particle if they were at `positions`.
+    pub fn accelerations(&self, positions: &[DVec3]) -> Vec<DVec3> {
+        let softening2 = self.softening * self.softening;
+        let mut accelerations = vec![DVec3::zeros(); positions.len()];
+
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let delta = positions[j] - positions[i];
+                let distance2 = delta.norm_squared() + softening2;
+                let inv_distance3 = 1.0 / (distance2 * distance2.sqrt());
+                let pull = delta * (self.gravitational_constant * inv_distance3);
+
+                accelerations[i] += pull * self.particles[j].mass;
+                accelerations[j] -= pull * self.particles[i].mass;
+            }
+        }
+
+        accelerations
+    }
+
+    pub fn kinetic_energy(&self) -> f64 {
+        self.particles
+            .iter()
+            .map(|p| 0.5 * p.mass * p.velocity.norm_squared())
+            .sum()
+    }
+
+    pub fn potential_energy(&self) -> f64 {
+        let softening2 = self.softening * self.softening;
+        let mut energy = 0.0;
+        for i in 0..self.particles.len() {
+            for j in (i + 1)..self.particles.len() {
+                let (a, b) = (&self.particles[i], &self.particles[j]);
+                let distance = ((b.position - a.position).norm_squared() + softening2).sqrt();
+                energy -= self.gravitational_constant * a.mass * b.mass / distance;
+            }
+        }
+        energy
+    }
+
+    pub fn total_energy(&self) -> f64 {
+        self.kinetic_energy() + self.potential_energy()
+    }
+
+    pub fn total_momentum(&self) -> DVec3 {
+        self.particles
+            .iter()
+            .map(|p| p.velocity * p.mass)
+            .fold(DVec3::zeros(), |sum, momentum| sum + momentum)
+    }
+
+    /// Shifts all velocities so the total momentum is zero, keeping the
+    /// system's barycenter in place.
+    pub fn remove_net_momentum(&mut self) {
+        let total_mass: f64 = self.particles.iter().map(|p| p.mass).sum();
+        if total_mass <= 0.0 {
+            return;
+        }
+        let drift = self.total_momentum() / total_mass;
+        for particle in &mut self.particles {
+            particle.velocity -= drift;
+        }
+    }
+}
+
+/// Conservation error of a system relative to a reference state.
+#[derive(Clone, Copy, Debug)]
+pub struct Drift {
+    /// `(E - E0) / |E0|`.
+    pub relative_energy: f64,
+    /// `|P - P0|`.
+    pub momentum: f64,
+}
+
+/// Remembers the energy and momentum of a system so later states can be
+/// checked against them.
+#[derive(Clone, Copy, Debug)]
+pub struct ConservationMonitor {
+    initial_energy: f64,
+    initial_momentum: DVec3,
+}
+
+impl ConservationMonitor {
+    pub fn new(system: &NBodySystem) -> Self {
+        ConservationMonitor {
+            initial_energy: system.total_energy(),
+            initial_momentum: system.total_momentum(),
+        }
+    }
+
+    pub fn drift(&self, system: &NBodySystem) -> Drift {
+        let energy = system.total_energy();
+        let relative_energy = if self.initial_energy != 0.0 {
+            (energy - self.initial_energy) / self.initial_energy.abs()
+        } else {
+            energy
+        };
+
+        Drift {
+            relative_energy,
+            momentum: (system.total_momentum() - self.initial_momentum).norm(),
+        }
+    }
+}
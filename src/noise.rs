@@ -0,0 +1,118 @@
+//! Fractal noise built on `FastNoiseLite`, sampled in 3D so patterns wrap
+//! around a sphere without UV seams or pinching at the poles.
+
+use crate::fragment::Fragment;
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::Vec3;
+use std::f32::consts::TAU;
+
+/// Octave settings shared by the fractal functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fractal {
+    pub octaves: u32,
+    /// Frequency multiplier between octaves.
+    pub lacunarity: f32,
+    /// Amplitude multiplier between octaves.
+    pub gain: f32,
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Fractal {
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl Fractal {
+    pub fn octaves(octaves: u32) -> Self {
+        Fractal {
+            octaves,
+            ..Fractal::default()
+        }
+    }
+}
+
+/// Where to sample surface noise for `fragment`: its direction from the
+/// body's center, scaled so that `zoom` features fit around the equator,
+/// matching the feature size of `zoom` across the UV range.
+pub fn surface_point(fragment: &Fragment, zoom: f32) -> Vec3 {
+    sphere_point(fragment.object_position, zoom)
+}
+
+/// [`surface_point`] for any object-space position, such as a vertex.
+pub fn sphere_point(position: Vec3, zoom: f32) -> Vec3 {
+    let direction = position.try_normalize(f32::EPSILON);
+    direction.unwrap_or_else(Vec3::zeros) * (zoom / TAU)
+}
+
+pub fn sample(noise: &FastNoiseLite, p: Vec3) -> f32 {
+    noise.get_noise_3d(p.x, p.y, p.z)
+}
+
+/// Fractal Brownian motion: octaves of noise summed with falling amplitude.
+/// Returns roughly `[-1, 1]`.
+pub fn fbm(noise: &FastNoiseLite, p: Vec3, fractal: Fractal) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+    for octave in 0..fractal.octaves {
+        // Offsetting each octave keeps their lattices from lining up at the
+        // origin.
+        let offset = octave as f32 * 17.31;
+        sum += amplitude * sample(noise, p * frequency + Vec3::repeat(offset));
+        total_amplitude += amplitude;
+        amplitude *= fractal.gain;
+        frequency *= fractal.lacunarity;
+    }
+    if total_amplitude > 0.0 {
+        sum / total_amplitude
+    } else {
+        0.0
+    }
+}
+
+/// Ridged multifractal: sharp crests where the noise crosses zero, as in
+/// mountain ranges and canyon walls. Returns `[0, 1]`.
+pub fn ridged(noise: &FastNoiseLite, p: Vec3, fractal: Fractal) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+    let mut weight = 1.0;
+    for octave in 0..fractal.octaves {
+        let offset = octave as f32 * 17.31;
+        let ridge = 1.0 - sample(noise, p * frequency + Vec3::repeat(offset)).abs();
+        let ridge = ridge * ridge * weight;
+        // Detail gathers on the crests of the previous octave.
+        weight = ridge.clamp(0.0, 1.0);
+        sum += amplitude * ridge;
+        total_amplitude += amplitude;
+        amplitude *= fractal.gain;
+        frequency *= fractal.lacunarity;
+    }
+    if total_amplitude > 0.0 {
+        (sum / total_amplitude).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Displaces `p` by a noise vector of length up to `strength`, for swirling,
+/// fluid-looking patterns.
+pub fn warp(noise: &FastNoiseLite, p: Vec3, strength: f32) -> Vec3 {
+    let offset = Vec3::new(
+        sample(noise, p + Vec3::new(5.2, 1.3, 7.7)),
+        sample(noise, p + Vec3::new(9.1, 3.7, 2.8)),
+        sample(noise, p + Vec3::new(1.7, 8.3, 4.1)),
+    );
+    p + offset * strength
+}
+
+/// fBm sampled at a domain-warped position.
+pub fn warped_fbm(noise: &FastNoiseLite, p: Vec3, strength: f32, fractal: Fractal) -> f32 {
+    fbm(noise, warp(noise, p, strength), fractal)
+}
@@ -0,0 +1,78 @@
+use nalgebra_glm::Vec3;
+use std::f64::consts::TAU;
+
+/// Classical Keplerian elements of an orbit around a parent body.
+///
+/// Angles are in radians and `period` is in seconds of simulation time.
+/// The reference plane is the renderer's XZ plane with +Y as the orbit
+/// normal, so a zero-inclination orbit lies flat in the ecliptic.
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub longitude_of_ascending_node: f32,
+    pub argument_of_periapsis: f32,
+    pub mean_anomaly_at_epoch: f32,
+    pub period: f32,
+}
+
+impl OrbitalElements {
+    /// Mean anomaly at `time`, wrapped to `[0, 2π)`.
+    pub fn mean_anomaly(&self, time: f64) -> f64 {
+        // f64 keeps the phase accurate after many thousands of orbits.
+        let phase = self.mean_anomaly_at_epoch as f64 + TAU * time / self.period as f64;
+        phase.rem_euclid(TAU)
+    }
+
+    /// Offset from the parent body at `time`.
+    pub fn position_at(&self, time: f64) -> Vec3 {
+        let e = self.eccentricity as f64;
+        let a = self.semi_major_axis as f64;
+        let eccentric_anomaly = solve_kepler(self.mean_anomaly(time), e);
+
+        // Position in the perifocal frame, periapsis along +x.
+        let (sin_e, cos_e) = eccentric_anomaly.sin_cos();
+        let x = a * (cos_e - e);
+        let y = a * (1.0 - e * e).sqrt() * sin_e;
+
+        let (sin_w, cos_w) = (self.argument_of_periapsis as f64).sin_cos();
+        let (sin_o, cos_o) = (self.longitude_of_ascending_node as f64).sin_cos();
+        let (sin_i, cos_i) = (self.inclination as f64).sin_cos();
+
+        // Rz(Ω) · Rx(i) · Rz(ω) applied to the perifocal position, giving
+        // ecliptic coordinates with +Z as the orbit normal.
+        let x1 = cos_w * x - sin_w * y;
+        let y1 = sin_w * x + cos_w * y;
+        let ecliptic_x = cos_o * x1 - sin_o * cos_i * y1;
+        let ecliptic_y = sin_o * x1 + cos_o * cos_i * y1;
+        let ecliptic_z = sin_i * y1;
+
+        // Ecliptic +Z is the renderer's +Y.
+        Vec3::new(ecliptic_x as f32, ecliptic_z as f32, -ecliptic_y as f32)
+    }
+}
+
+/// Solves Kepler's equation `M = E - e·sin(E)` for the eccentric anomaly `E`
+/// using Newton's method. Valid for elliptical orbits (`0 <= e < 1`).
+pub fn solve_kepler(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    // Starting from M converges slowly for very eccentric orbits; π is a
+    // safe starting point there.
+    let mut eccentric_anomaly = if eccentricity > 0.8 {
+        std::f64::consts::PI
+    } else {
+        mean_anomaly
+    };
+
+    for _ in 0..32 {
+        let f = eccentric_anomaly - eccentricity * eccentric_anomaly.sin() - mean_anomaly;
+        let derivative = 1.0 - eccentricity * eccentric_anomaly.cos();
+        let step = f / derivative;
+        eccentric_anomaly -= step;
+        if step.abs() < 1e-12 {
+            break;
+        }
+    }
+
+    eccentric_anomaly
+}
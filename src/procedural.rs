@@ -0,0 +1,364 @@
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::noise::{fbm, surface_point, warped_fbm, Fractal};
+use crate::shaders::{apply_enhanced_lighting, FragmentShader};
+use crate::Uniforms;
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use nalgebra_glm::Vec3;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::{PI, TAU};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanetClass {
+    Rocky,
+    Ocean,
+    Desert,
+    Ice,
+    Lava,
+    GasGiant,
+    IceGiant,
+}
+
+impl PlanetClass {
+    pub const ALL: [PlanetClass; 7] = [
+        PlanetClass::Rocky,
+        PlanetClass::Ocean,
+        PlanetClass::Desert,
+        PlanetClass::Ice,
+        PlanetClass::Lava,
+        PlanetClass::GasGiant,
+        PlanetClass::IceGiant,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlanetClass::Rocky => "rocky",
+            PlanetClass::Ocean => "ocean",
+            PlanetClass::Desert => "desert",
+            PlanetClass::Ice => "ice",
+            PlanetClass::Lava => "lava",
+            PlanetClass::GasGiant => "gas_giant",
+            PlanetClass::IceGiant => "ice_giant",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PlanetClass::ALL.into_iter().find(|class| class.name() == name)
+    }
+
+    pub fn is_giant(&self) -> bool {
+        matches!(self, PlanetClass::GasGiant | PlanetClass::IceGiant)
+    }
+}
+
+/// Surface colors, as sRGB-encoded components in `[0, 1]`; the shader decodes
+/// them with [`Color::from_srgb`].
+///
+/// Solid worlds use `sea` below the sea level, blend `lowland` to `highland`
+/// with height and put `peak` on mountains and ice caps. Giants alternate
+/// `lowland` and `highland` bands and paint storms with `peak`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub sea: [f32; 3],
+    pub lowland: [f32; 3],
+    pub highland: [f32; 3],
+    pub peak: [f32; 3],
+    pub cloud: [f32; 3],
+}
+
+/// An oval storm on a giant. `longitude` and `latitude` are fractions of a
+/// turn and of the pole-to-pole angle, `radius` is in texture units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Storm {
+    pub longitude: f32,
+    pub latitude: f32,
+    pub radius: f32,
+}
+
+/// Everything that sets one procedural world apart from another.
+///
+/// Heights and thresholds are in noise units, `[-1, 1]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanetParams {
+    pub class: PlanetClass,
+    pub seed: u64,
+    pub palette: Palette,
+    /// Noise frequency over the texture coordinates.
+    pub zoom: f32,
+    /// Terrain below this is sea (molten on lava worlds). `-1` for none.
+    pub sea_level: f32,
+    pub mountain_level: f32,
+    /// Distance from the equator, `0` to `1`, beyond which the surface is
+    /// ice. Above `1` for no caps.
+    pub ice_cap_latitude: f32,
+    /// Fraction of the sky covered by cloud, `0` to `1`.
+    pub cloud_cover: f32,
+    pub cloud_zoom: f32,
+    pub cloud_speed: f32,
+    /// Number of bands from pole to pole on giants; `0` on solid worlds.
+    pub band_count: f32,
+    /// How strongly noise bends the bands.
+    pub band_turbulence: f32,
+    pub storms: Vec<Storm>,
+    pub intensity: f32,
+}
+
+impl PlanetParams {
+    /// Generates a world of `class`. The same class and seed always give the
+    /// same parameters, on any platform and across dependency updates:
+    /// ChaCha8 is used rather than `StdRng`, whose algorithm may change
+    /// between `rand` releases.
+    pub fn generate(class: PlanetClass, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut jitter = |color: [f32; 3], amount: f32| -> [f32; 3] {
+            color.map(|channel| (channel + rng.gen_range(-amount..=amount)).clamp(0.0, 1.0))
+        };
+
+        let palette = match class {
+            PlanetClass::Rocky => Palette {
+                sea: jitter([0.25, 0.22, 0.2], 0.05),
+                lowland: jitter([0.45, 0.38, 0.32], 0.1),
+                highland: jitter([0.62, 0.55, 0.48], 0.1),
+                peak: jitter([0.8, 0.78, 0.75], 0.05),
+                cloud: jitter([0.85, 0.82, 0.78], 0.05),
+            },
+            PlanetClass::Ocean => Palette {
+                sea: jitter([0.08, 0.3, 0.7], 0.08),
+                lowland: jitter([0.2, 0.55, 0.2], 0.1),
+                highland: jitter([0.45, 0.4, 0.25], 0.1),
+                peak: jitter([0.95, 0.95, 0.97], 0.03),
+                cloud: jitter([0.97, 0.97, 1.0], 0.03),
+            },
+            PlanetClass::Desert => Palette {
+                sea: jitter([0.55, 0.4, 0.25], 0.05),
+                lowland: jitter([0.85, 0.68, 0.42], 0.08),
+                highland: jitter([0.7, 0.45, 0.25], 0.08),
+                peak: jitter([0.55, 0.35, 0.2], 0.05),
+                cloud: jitter([0.9, 0.8, 0.65], 0.05),
+            },
+            PlanetClass::Ice => Palette {
+                sea: jitter([0.55, 0.7, 0.85], 0.05),
+                lowland: jitter([0.85, 0.9, 0.95], 0.04),
+                highland: jitter([0.7, 0.78, 0.88], 0.05),
+                peak: jitter([1.0, 1.0, 1.0], 0.0),
+                cloud: jitter([0.95, 0.97, 1.0], 0.03),
+            },
+            PlanetClass::Lava => Palette {
+                sea: jitter([1.0, 0.35, 0.05], 0.08),
+                lowland: jitter([0.15, 0.1, 0.08], 0.04),
+                highland: jitter([0.28, 0.2, 0.16], 0.05),
+                peak: jitter([0.4, 0.32, 0.28], 0.05),
+                cloud: jitter([0.3, 0.28, 0.27], 0.05),
+            },
+            PlanetClass::GasGiant => Palette {
+                sea: jitter([0.7, 0.55, 0.4], 0.1),
+                lowland: jitter([0.9, 0.75, 0.55], 0.1),
+                highland: jitter([0.65, 0.42, 0.28], 0.1),
+                peak: jitter([0.85, 0.35, 0.2], 0.1),
+                cloud: jitter([1.0, 0.95, 0.85], 0.05),
+            },
+            PlanetClass::IceGiant => Palette {
+                sea: jitter([0.3, 0.6, 0.8], 0.08),
+                lowland: jitter([0.5, 0.8, 0.9], 0.08),
+                highland: jitter([0.35, 0.6, 0.85], 0.08),
+                peak: jitter([0.2, 0.35, 0.7], 0.08),
+                cloud: jitter([0.9, 0.97, 1.0], 0.03),
+            },
+        };
+
+        let (sea_level, mountain_level, ice_cap_latitude, cloud_cover) = match class {
+            PlanetClass::Rocky => (-1.0, rng.gen_range(0.3..0.6), 2.0, rng.gen_range(0.0..0.15)),
+            PlanetClass::Ocean => (
+                rng.gen_range(0.0..0.3),
+                rng.gen_range(0.5..0.75),
+                rng.gen_range(0.7..0.9),
+                rng.gen_range(0.3..0.65),
+            ),
+            PlanetClass::Desert => (-1.0, rng.gen_range(0.4..0.7), 2.0, rng.gen_range(0.0..0.1)),
+            PlanetClass::Ice => (
+                rng.gen_range(-0.6..-0.2),
+                rng.gen_range(0.3..0.6),
+                rng.gen_range(0.3..0.6),
+                rng.gen_range(0.1..0.35),
+            ),
+            PlanetClass::Lava => (
+                rng.gen_range(-0.3..0.1),
+                rng.gen_range(0.4..0.7),
+                2.0,
+                rng.gen_range(0.05..0.35),
+            ),
+            PlanetClass::GasGiant | PlanetClass::IceGiant => {
+                (-1.0, 1.0, 2.0, rng.gen_range(0.0..0.3))
+            }
+        };
+
+        let (band_count, band_turbulence, storm_count) = match class {
+            PlanetClass::GasGiant => (
+                rng.gen_range(8..=18) as f32,
+                rng.gen_range(0.2..0.6),
+                rng.gen_range(1..=4),
+            ),
+            PlanetClass::IceGiant => (
+                rng.gen_range(2..=7) as f32,
+                rng.gen_range(0.05..0.25),
+                rng.gen_range(0..=2),
+            ),
+            _ => (0.0, 0.0, 0),
+        };
+        let storms = (0..storm_count)
+            .map(|_| Storm {
+                longitude: rng.gen_range(0.0..1.0),
+                latitude: rng.gen_range(0.25..0.75),
+                radius: rng.gen_range(0.03..0.09),
+            })
+            .collect();
+
+        PlanetParams {
+            class,
+            seed,
+            palette,
+            zoom: rng.gen_range(3.0..8.0),
+            sea_level,
+            mountain_level,
+            ice_cap_latitude,
+            cloud_cover,
+            cloud_zoom: rng.gen_range(4.0..10.0),
+            cloud_speed: rng.gen_range(0.01..0.05),
+            band_count,
+            band_turbulence,
+            storms,
+            intensity: rng.gen_range(1.3..1.8),
+        }
+    }
+}
+
+/// Draws a world from [`PlanetParams`], with noise seeded from the world's
+/// own seed so no two worlds share a surface.
+pub struct ProceduralPlanetShader {
+    params: PlanetParams,
+    noise: FastNoiseLite,
+}
+
+impl ProceduralPlanetShader {
+    pub fn new(params: PlanetParams) -> Self {
+        // FastNoiseLite takes an i32 seed; fold the high bits in.
+        let mut noise = FastNoiseLite::with_seed((params.seed ^ (params.seed >> 32)) as i32);
+        noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+        noise.set_frequency(Some(1.0));
+        ProceduralPlanetShader { params, noise }
+    }
+
+    pub fn params(&self) -> &PlanetParams {
+        &self.params
+    }
+
+    fn solid_surface(&self, fragment: &Fragment) -> ([f32; 3], bool) {
+        let params = &self.params;
+        let palette = &params.palette;
+        let height = fbm(&self.noise, surface_point(fragment, params.zoom), Fractal::default());
+        let latitude = (polar_angle(fragment) * 2.0 - 1.0).abs();
+
+        if latitude > params.ice_cap_latitude {
+            return (palette.peak, false);
+        }
+        if height < params.sea_level {
+            return (palette.sea, params.class == PlanetClass::Lava);
+        }
+        if height > params.mountain_level {
+            return (palette.peak, false);
+        }
+
+        let t = (height - params.sea_level.max(-1.0))
+            / (params.mountain_level - params.sea_level.max(-1.0));
+        (mix(palette.lowland, palette.highland, t), false)
+    }
+
+    fn giant_surface(&self, fragment: &Fragment) -> [f32; 3] {
+        let params = &self.params;
+        let palette = &params.palette;
+        let v = polar_angle(fragment);
+
+        // Squashing the noise along the axis streaks it out along the bands.
+        let p = surface_point(fragment, params.zoom).component_mul(&Vec3::new(1.0, 4.0, 1.0));
+        let bend = warped_fbm(&self.noise, p, 0.5, Fractal::default());
+        let band = ((v * params.band_count + bend * params.band_turbulence) * PI).sin() * 0.5 + 0.5;
+        let mut color = mix(palette.lowland, palette.highland, band);
+
+        let direction = fragment.object_position.try_normalize(f32::EPSILON).unwrap_or_else(Vec3::y);
+        for storm in &params.storms {
+            let (theta, phi) = (storm.latitude * PI, storm.longitude * TAU);
+            let center = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            if direction.dot(&center) <= 0.0 {
+                continue;
+            }
+            // Offsets along the storm's local east and south, measured in
+            // texture units so `radius` keeps its meaning. Storms are ovals
+            // twice as wide as they are tall.
+            let east = Vec3::new(-phi.sin(), 0.0, phi.cos());
+            let south = Vec3::new(theta.cos() * phi.cos(), -theta.sin(), theta.cos() * phi.sin());
+            let du = direction.dot(&east) / TAU;
+            let dv = direction.dot(&south) / PI * 2.0;
+            let distance = (du * du + dv * dv).sqrt() / storm.radius;
+            if distance < 1.0 {
+                color = mix(color, palette.peak, (1.0 - distance).min(0.5) * 2.0);
+            }
+        }
+        color
+    }
+}
+
+impl FragmentShader for ProceduralPlanetShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let params = &self.params;
+        let (surface, emissive) = if params.class.is_giant() {
+            (self.giant_surface(fragment), false)
+        } else {
+            self.solid_surface(fragment)
+        };
+
+        // Clouds drift by turning the sample point about the spin axis.
+        let drift = uniforms.time * params.cloud_speed * TAU;
+        let p = surface_point(fragment, params.cloud_zoom);
+        let (sin, cos) = drift.sin_cos();
+        let drifted = Vec3::new(p.x * cos - p.z * sin, p.y, p.x * sin + p.z * cos);
+        let cloud_noise = fbm(&self.noise, drifted + Vec3::repeat(100.0), Fractal::default());
+        // Noise is roughly symmetric around zero, so this threshold covers
+        // about `cloud_cover` of the surface.
+        let threshold = 1.0 - params.cloud_cover * 2.0;
+        let cloud_alpha = ((cloud_noise - threshold) * 4.0).clamp(0.0, 1.0);
+        let color = mix(surface, params.palette.cloud, cloud_alpha);
+
+        let [r, g, b] = color;
+        let lit = apply_enhanced_lighting(
+            fragment,
+            uniforms,
+            sun_position,
+            Color::from_srgb(r, g, b),
+            params.intensity,
+        );
+        if emissive && cloud_alpha < 1.0 {
+            // Molten seas glow on the night side too.
+            let [r, g, b] = surface;
+            lit.blend_add(&(Color::from_srgb(r, g, b) * (0.6 * (1.0 - cloud_alpha))))
+        } else {
+            lit
+        }
+    }
+}
+
+/// Angle from the north pole as a fraction of π: 0 at the north pole, 1 at
+/// the south pole.
+fn polar_angle(fragment: &Fragment) -> f32 {
+    let direction = fragment.object_position.try_normalize(f32::EPSILON).unwrap_or_else(Vec3::y);
+    direction.y.clamp(-1.0, 1.0).acos() / PI
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0);
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
@@ -0,0 +1,512 @@
+use crate::clipping::{clip_triangle, project_to_screen};
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::shaders::FragmentShader;
+use crate::shadow::Shadows;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::triangle::{calculate_bounding_box, rasterize, FragmentSink, PixelRect, RasterState};
+use crate::vertex::Vertex;
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use nalgebra_glm::{look_at, perspective, Mat4, Vec3};
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+pub struct Uniforms {
+    pub model_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    /// World-space eye position, for view-dependent shading.
+    pub camera_position: Vec3,
+    /// Simulation time in seconds, used to animate the shaders.
+    pub time: f32,
+    pub noise: FastNoiseLite,
+    /// Bodies that can block the Sun from a fragment.
+    pub shadows: Shadows,
+}
+
+impl Uniforms {
+    /// Uniforms for drawing into a `width` by `height` framebuffer: identity
+    /// model and view matrices, a perspective projection and viewport for
+    /// that size, the eye at the origin, time zero, the default noise and no
+    /// shadows.
+    pub fn new(width: f32, height: f32) -> Self {
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: create_perspective_matrix(width, height),
+            viewport_matrix: create_viewport_matrix(width, height),
+            camera_position: Vec3::zeros(),
+            time: 0.0,
+            noise: create_noise(),
+            shadows: Shadows::default(),
+        }
+    }
+}
+
+pub fn create_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(1337);
+    noise.set_noise_type(Some(NoiseType::Cellular));
+    noise.set_frequency(Some(0.1));
+    noise
+}
+
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0, 0.0, 0.0, 0.0, 
+        0.0, cos_x, -sin_x, 0.0, 
+        0.0, sin_x, cos_x, 0.0, 
+        0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y, 0.0, sin_y, 0.0, 
+        0.0, 1.0, 0.0, 0.0, 
+        -sin_y, 0.0, cos_y, 0.0, 
+        0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0, 
+        sin_z, cos_z, 0.0, 0.0, 
+        0.0, 0.0, 1.0, 0.0, 
+        0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let scale_matrix = Mat4::new(
+        scale, 0.0, 0.0, 0.0, 
+        0.0, scale, 0.0, 0.0, 
+        0.0, 0.0, scale, 0.0, 
+        0.0, 0.0, 0.0, 1.0,
+    );
+
+    let translation_matrix = Mat4::new(
+        1.0,
+        0.0,
+        0.0,
+        translation.x, 
+        0.0,
+        1.0,
+        0.0,
+        translation.y, 
+        0.0,
+        0.0,
+        1.0,
+        translation.z, 
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    translation_matrix * rotation_matrix * scale_matrix
+}
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
+    let fov = 45.0 * PI / 180.0;
+    let aspect_ratio = window_width / window_height;
+    let near = 0.1;
+    let far = 1000.0;
+
+    perspective(fov, aspect_ratio, near, far)
+}
+
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0,
+        0.0,
+        0.0,
+        width / 2.0, 
+        0.0,
+        -height / 2.0,
+        0.0,
+        height / 2.0, 
+        0.0,
+        0.0,
+        1.0,
+        0.0, 
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+/// Edge length in pixels of the square tiles the framebuffer is split into
+/// for rasterization.
+pub const TILE_SIZE: usize = 64;
+
+/// Edge length in pixels of the blocks each tile's coarse depth buffer
+/// tracks.
+const COARSE_BLOCK: usize = 8;
+
+/// What a call to [`render`] drew.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Triangles left after clipping and face culling.
+    pub triangles: usize,
+    /// Triangle/tile pairs skipped by the coarse depth test without being
+    /// rasterized.
+    pub coarse_rejections: usize,
+}
+
+/// A screen region and the triangles whose bounding boxes overlap it, in
+/// submission order.
+struct Tile {
+    bounds: PixelRect,
+    triangles: Vec<usize>,
+}
+
+/// Color and depth of one tile, copied out of the framebuffer so threads can
+/// work on disjoint tiles without sharing it.
+struct TileBuffer {
+    tile: usize,
+    color: Vec<Color>,
+    depth: Vec<f32>,
+    coarse_rejections: usize,
+}
+
+/// Draws `vertex_array` as a triangle list into `framebuffer`, blending and
+/// writing depth as the framebuffer is set to.
+pub fn render(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    shader: &dyn FragmentShader,
+    sun_position: Vec3,
+    raster_state: RasterState,
+) -> RenderStats {
+    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
+    for vertex in vertex_array {
+        let transformed = shader.vertex(vertex, uniforms);
+        transformed_vertices.push(transformed);
+    }
+
+    let triangles = assemble_triangles(&transformed_vertices, uniforms, raster_state);
+    let tiles = bin_triangles(&triangles, framebuffer.width, framebuffer.height);
+
+    let shade = |fragment: &Fragment| shader.fragment(fragment, uniforms, sun_position);
+
+    // Each pixel belongs to exactly one tile and sees its triangles in
+    // submission order, so the result does not depend on how tiles are
+    // spread across threads.
+    let mut stats = RenderStats {
+        triangles: triangles.len(),
+        coarse_rejections: 0,
+    };
+    let threads = framebuffer.render_threads().min(tiles.len());
+    if threads <= 1 {
+        for index in 0..tiles.len() {
+            let buffer = rasterize_tile(framebuffer, &tiles, index, &triangles, &shade);
+            stats.coarse_rejections += buffer.coarse_rejections;
+            write_tile(framebuffer, &tiles[index], buffer);
+        }
+        return stats;
+    }
+
+    let next_tile = AtomicUsize::new(0);
+    let buffers: Vec<TileBuffer> = thread::scope(|scope| {
+        let framebuffer = &*framebuffer;
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut finished = Vec::new();
+                    loop {
+                        let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                        if index >= tiles.len() {
+                            break finished;
+                        }
+                        finished.push(rasterize_tile(framebuffer, &tiles, index, &triangles, &shade));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("render thread panicked"))
+            .collect()
+    });
+
+    for buffer in buffers {
+        stats.coarse_rejections += buffer.coarse_rejections;
+        let tile = &tiles[buffer.tile];
+        write_tile(framebuffer, tile, buffer);
+    }
+    stats
+}
+
+/// Clips, projects and culls every triangle, fanning clipped polygons back
+/// into screen-space triangles.
+fn assemble_triangles(
+    vertices: &[Vertex],
+    uniforms: &Uniforms,
+    raster_state: RasterState,
+) -> Vec<[Vertex; 3]> {
+    let mut triangles = Vec::new();
+    for tri in vertices.chunks(3) {
+        let mut polygon = clip_triangle(&tri[0], &tri[1], &tri[2]);
+        for vertex in &mut polygon {
+            project_to_screen(vertex, &uniforms.viewport_matrix);
+        }
+        // Clipping keeps the polygon planar, so its first fan triangle
+        // decides the facing of the whole polygon.
+        if polygon.len() < 3
+            || raster_state.culls(
+                &polygon[0].transformed_position,
+                &polygon[1].transformed_position,
+                &polygon[2].transformed_position,
+            )
+        {
+            continue;
+        }
+        for i in 1..polygon.len() - 1 {
+            triangles.push([polygon[0].clone(), polygon[i].clone(), polygon[i + 1].clone()]);
+        }
+    }
+    triangles
+}
+
+/// Splits the framebuffer into tiles and lists, for each tile, the triangles
+/// that may cover it. Tiles no triangle touches are left out.
+fn bin_triangles(triangles: &[[Vertex; 3]], width: usize, height: usize) -> Vec<Tile> {
+    let columns = width.div_ceil(TILE_SIZE);
+    let rows = height.div_ceil(TILE_SIZE);
+    let mut bins: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+
+    for (index, [a, b, c]) in triangles.iter().enumerate() {
+        let (min_x, min_y, max_x, max_y) = calculate_bounding_box(
+            &a.transformed_position,
+            &b.transformed_position,
+            &c.transformed_position,
+        );
+        if max_x < 0 || max_y < 0 || min_x >= width as i32 || min_y >= height as i32 {
+            continue;
+        }
+        let first_column = min_x.max(0) as usize / TILE_SIZE;
+        let first_row = min_y.max(0) as usize / TILE_SIZE;
+        let last_column = (max_x as usize).min(width - 1) / TILE_SIZE;
+        let last_row = (max_y as usize).min(height - 1) / TILE_SIZE;
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                bins[row * columns + column].push(index);
+            }
+        }
+    }
+
+    bins.into_iter()
+        .enumerate()
+        .filter(|(_, triangles)| !triangles.is_empty())
+        .map(|(bin, triangles)| {
+            let x = (bin % columns) * TILE_SIZE;
+            let y = (bin / columns) * TILE_SIZE;
+            Tile {
+                bounds: PixelRect {
+                    min_x: x as i32,
+                    min_y: y as i32,
+                    max_x: ((x + TILE_SIZE).min(width) - 1) as i32,
+                    max_y: ((y + TILE_SIZE).min(height) - 1) as i32,
+                },
+                triangles,
+            }
+        })
+        .collect()
+}
+
+fn rasterize_tile(
+    framebuffer: &Framebuffer,
+    tiles: &[Tile],
+    index: usize,
+    triangles: &[[Vertex; 3]],
+    shade: &(impl Fn(&Fragment) -> Color + Sync),
+) -> TileBuffer {
+    let tile = &tiles[index];
+    let bounds = &tile.bounds;
+    let tile_width = bounds.width();
+
+    let mut color = Vec::with_capacity(tile_width * bounds.height());
+    let mut depth = Vec::with_capacity(tile_width * bounds.height());
+    for y in bounds.min_y as usize..=bounds.max_y as usize {
+        let row = y * framebuffer.width;
+        let span = row + bounds.min_x as usize..=row + bounds.max_x as usize;
+        color.extend_from_slice(&framebuffer.color[span.clone()]);
+        depth.extend_from_slice(&framebuffer.zbuffer[span]);
+    }
+
+    let mut target = TileTarget {
+        bounds,
+        coarse_depth: CoarseDepth::new(bounds, &depth),
+        color: &mut color,
+        depth: &mut depth,
+        shade,
+        blend_mode: framebuffer.blend_mode(),
+        depth_write: framebuffer.depth_write(),
+        written: false,
+    };
+    let mut coarse_rejections = 0;
+    for &triangle_index in &tile.triangles {
+        let [v1, v2, v3] = &triangles[triangle_index];
+        let (a, b, c) = (
+            &v1.transformed_position,
+            &v2.transformed_position,
+            &v3.transformed_position,
+        );
+        let (min_x, min_y, max_x, max_y) = calculate_bounding_box(a, b, c);
+        let area = PixelRect {
+            min_x: min_x.max(bounds.min_x),
+            min_y: min_y.max(bounds.min_y),
+            max_x: max_x.min(bounds.max_x),
+            max_y: max_y.min(bounds.max_y),
+        };
+        if area.min_x > area.max_x || area.min_y > area.max_y {
+            continue;
+        }
+
+        // Depth is linear in screen space, so no fragment is nearer than
+        // the nearest vertex.
+        let nearest = a.z.min(b.z).min(c.z);
+        if target.coarse_depth.occludes(&area, nearest) {
+            coarse_rejections += 1;
+            continue;
+        }
+
+        target.written = false;
+        rasterize(v1, v2, v3, bounds, &mut target);
+        if target.written {
+            target.coarse_depth.update(&area, target.depth);
+        }
+    }
+
+    TileBuffer {
+        tile: index,
+        color,
+        depth,
+        coarse_rejections,
+    }
+}
+
+/// Farthest depth in each `COARSE_BLOCK`-sized block of a tile. A triangle
+/// no nearer than the farthest pixel of every block it overlaps cannot pass
+/// the depth test anywhere, so it can be skipped without rasterizing it.
+struct CoarseDepth {
+    origin_x: usize,
+    origin_y: usize,
+    tile_width: usize,
+    tile_height: usize,
+    columns: usize,
+    farthest: Vec<f32>,
+}
+
+impl CoarseDepth {
+    fn new(bounds: &PixelRect, depth: &[f32]) -> Self {
+        let tile_width = bounds.width();
+        let tile_height = bounds.height();
+        let columns = tile_width.div_ceil(COARSE_BLOCK);
+        let rows = tile_height.div_ceil(COARSE_BLOCK);
+        let mut coarse = CoarseDepth {
+            origin_x: bounds.min_x as usize,
+            origin_y: bounds.min_y as usize,
+            tile_width,
+            tile_height,
+            columns,
+            farthest: vec![f32::INFINITY; columns * rows],
+        };
+        coarse.update(bounds, depth);
+        coarse
+    }
+
+    /// Blocks overlapping `area`, as inclusive column and row ranges.
+    fn blocks(&self, area: &PixelRect) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        let columns = (area.min_x as usize - self.origin_x) / COARSE_BLOCK
+            ..=(area.max_x as usize - self.origin_x) / COARSE_BLOCK;
+        let rows = (area.min_y as usize - self.origin_y) / COARSE_BLOCK
+            ..=(area.max_y as usize - self.origin_y) / COARSE_BLOCK;
+        (columns, rows)
+    }
+
+    fn occludes(&self, area: &PixelRect, nearest: f32) -> bool {
+        let (columns, rows) = self.blocks(area);
+        rows.into_iter().all(|row| {
+            columns
+                .clone()
+                .all(|column| nearest >= self.farthest[row * self.columns + column])
+        })
+    }
+
+    /// Recomputes the blocks overlapping `area` from the tile's depth buffer.
+    fn update(&mut self, area: &PixelRect, depth: &[f32]) {
+        let (columns, rows) = self.blocks(area);
+        for row in rows {
+            for column in columns.clone() {
+                let x0 = column * COARSE_BLOCK;
+                let y0 = row * COARSE_BLOCK;
+                let x1 = (x0 + COARSE_BLOCK).min(self.tile_width);
+                let y1 = (y0 + COARSE_BLOCK).min(self.tile_height);
+                let mut farthest = f32::NEG_INFINITY;
+                for y in y0..y1 {
+                    let line = &depth[y * self.tile_width + x0..y * self.tile_width + x1];
+                    farthest = line.iter().fold(farthest, |acc, &d| acc.max(d));
+                }
+                self.farthest[row * self.columns + column] = farthest;
+            }
+        }
+    }
+}
+
+/// Shades fragments straight into a tile's buffers, depth-testing them
+/// first so hidden fragments are never shaded.
+struct TileTarget<'a, S> {
+    bounds: &'a PixelRect,
+    coarse_depth: CoarseDepth,
+    color: &'a mut [Color],
+    depth: &'a mut [f32],
+    shade: &'a S,
+    blend_mode: BlendMode,
+    depth_write: bool,
+    /// Whether the current triangle wrote any depth.
+    written: bool,
+}
+
+impl<S> TileTarget<'_, S> {
+    fn index(&self, x: usize, y: usize) -> usize {
+        (y - self.bounds.min_y as usize) * self.bounds.width() + x - self.bounds.min_x as usize
+    }
+}
+
+impl<S: Fn(&Fragment) -> Color> FragmentSink for TileTarget<'_, S> {
+    fn test(&mut self, x: usize, y: usize, depth: f32) -> bool {
+        // Same comparison as `Framebuffer::point`.
+        self.depth[self.index(x, y)] > depth
+    }
+
+    fn fragment(&mut self, fragment: Fragment) {
+        let index = self.index(fragment.position.x as usize, fragment.position.y as usize);
+        self.color[index] = self.blend_mode.blend((self.shade)(&fragment), self.color[index]);
+        if self.depth_write {
+            self.depth[index] = fragment.depth;
+            self.written = true;
+        }
+    }
+}
+
+fn write_tile(framebuffer: &mut Framebuffer, tile: &Tile, buffer: TileBuffer) {
+    let bounds = &tile.bounds;
+    let tile_width = bounds.width();
+    for (row, y) in (bounds.min_y as usize..=bounds.max_y as usize).enumerate() {
+        let start = y * framebuffer.width + bounds.min_x as usize;
+        let local = row * tile_width..(row + 1) * tile_width;
+        framebuffer.color[start..start + tile_width].copy_from_slice(&buffer.color[local.clone()]);
+        framebuffer.zbuffer[start..start + tile_width].copy_from_slice(&buffer.depth[local]);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::atmosphere::Atmosphere;
+use crate::camera::Camera;
+use crate::clipping::Frustum;
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::lod::{projected_radius, LodMeshes};
+use crate::nbody::{ConservationMonitor, Drift, Integrator, NBodySystem, Particle};
+use crate::orbit::OrbitalElements;
+use crate::render::{create_model_matrix, create_view_matrix, render, RenderStats, Uniforms};
+use crate::shaders::FragmentShader;
+use crate::shadow::{Occluder, Shadows};
+use crate::triangle::RasterState;
+use crate::vertex::Vertex;
+use nalgebra_glm::{DVec3, Vec3};
+
+pub struct Ring {
+    pub vertex_arrays: Vec<Vertex>,
+    pub scale: f32,
+    pub rotation: Vec3,
+    /// Rings are thin sheets seen from both sides, so usually double-sided.
+    pub raster_state: RasterState,
+    /// Anything but `Replace` draws the ring in the translucent pass.
+    pub blend_mode: BlendMode,
+}
+
+impl Ring {
+    /// Distance from the body's center to the ring's outer edge, in body
+    /// radii.
+    pub fn radius(&self) -> f32 {
+        let furthest = self
+            .vertex_arrays
+            .iter()
+            .map(|vertex| vertex.position.magnitude())
+            .fold(0.0, f32::max);
+        furthest * self.scale
+    }
+}
+
+pub struct CelestialBody {
+    pub name: String,
+    /// Index of the body this one is positioned relative to.
+    pub parent: Option<usize>,
+    /// Offset from the parent, or from the origin for top-level bodies.
+    /// Ignored when the body has an orbit.
+    pub local_position: Vec3,
+    pub orbit: Option<OrbitalElements>,
+    /// Mass in scene units; only used in N-body mode.
+    pub mass: f32,
+    /// World-space position, recomputed by `Scene::step`.
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation: Vec3,
+    /// The body's own meshes, or `None` to use the scene's shared sphere.
+    pub mesh: Option<LodMeshes>,
+    /// Level of detail drawn last frame, kept so level changes can lag
+    /// behind size changes.
+    pub lod: usize,
+    pub shader: Box<dyn FragmentShader>,
+    pub raster_state: RasterState,
+    /// Anything but `Replace` draws the body in the translucent pass.
+    pub blend_mode: BlendMode,
+    pub visible: bool,
+    pub ring: Option<Ring>,
+    /// Scattering shell drawn over the body in the translucent pass.
+    pub atmosphere: Option<Atmosphere>,
+}
+
+/// What a call to [`Scene::render`] drew.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Bodies drawn, not counting their rings.
+    pub bodies: usize,
+    /// Visible bodies skipped because they lie outside the view frustum.
+    pub culled: usize,
+    /// Triangles left after clipping and face culling, rings included.
+    pub triangles: usize,
+    pub coarse_rejections: usize,
+}
+
+/// How body positions are advanced over time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulationMode {
+    /// Bodies follow their Keplerian orbits exactly.
+    Rails,
+    /// Bodies are integrated under mutual gravity.
+    NBody(Integrator),
+}
+
+/// Integration substeps per `Scene::step` in N-body mode.
+const NBODY_SUBSTEPS: u32 = 8;
+
+struct Physics {
+    system: NBodySystem,
+    integrator: Integrator,
+    monitor: ConservationMonitor,
+}
+
+/// A set of celestial bodies, by default sharing one set of sphere meshes,
+/// lit by a single sun.
+///
+/// Usually loaded from a scene file with [`crate::scene_file::load_scene`].
+pub struct Scene {
+    pub bodies: Vec<CelestialBody>,
+    /// Spheres drawn for bodies without meshes of their own.
+    pub sphere: LodMeshes,
+    pub sun_position: Vec3,
+    /// Radius of the Sun's disc, which sets how wide the penumbrae of
+    /// shadows cast between bodies are; 0 gives hard edges.
+    pub sun_radius: f32,
+    /// Gravitational constant used in N-body mode, in scene units.
+    pub gravitational_constant: f32,
+    time: f64,
+    physics: Option<Physics>,
+}
+
+impl Scene {
+    pub fn new(bodies: Vec<CelestialBody>, sphere: LodMeshes, sun_position: Vec3) -> Self {
+        let mut scene = Scene {
+            bodies,
+            sphere,
+            sun_position,
+            sun_radius: 0.0,
+            gravitational_constant: 1.0,
+            time: 0.0,
+            physics: None,
+        };
+        scene.place_bodies();
+        scene
+    }
+
+    /// Simulation time in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn mode(&self) -> SimulationMode {
+        match &self.physics {
+            Some(physics) => SimulationMode::NBody(physics.integrator),
+            None => SimulationMode::Rails,
+        }
+    }
+
+    /// Switches simulation mode.
+    ///
+    /// Entering N-body mode seeds every body with its on-rails position and
+    /// velocity at the current time, so the simulation starts from the
+    /// current layout. Changing integrators keeps the current N-body state.
+    pub fn set_mode(&mut self, mode: SimulationMode) {
+        match (mode, &mut self.physics) {
+            (SimulationMode::Rails, _) => self.physics = None,
+            (SimulationMode::NBody(integrator), Some(physics)) => {
+                physics.integrator = integrator;
+            }
+            (SimulationMode::NBody(integrator), None) => {
+                let system = self.rails_system();
+                self.physics = Some(Physics {
+                    monitor: ConservationMonitor::new(&system),
+                    system,
+                    integrator,
+                });
+            }
+        }
+        self.place_bodies();
+    }
+
+    /// Energy and momentum drift since N-body mode was entered.
+    pub fn drift(&self) -> Option<Drift> {
+        self.physics
+            .as_ref()
+            .map(|physics| physics.monitor.drift(&physics.system))
+    }
+
+    /// Advances the simulation by `dt` seconds; negative `dt` runs it
+    /// backwards.
+    ///
+    /// Meant to be called with the fixed step of a
+    /// [`SimulationClock`](crate::clock::SimulationClock): rails positions are
+    /// exact for any `dt`, but N-body accuracy depends on it.
+    pub fn step(&mut self, dt: f64) {
+        self.time += dt;
+        if let Some(physics) = &mut self.physics {
+            let substep = dt / NBODY_SUBSTEPS as f64;
+            for _ in 0..NBODY_SUBSTEPS {
+                physics.system.step(substep, physics.integrator);
+            }
+        }
+        self.place_bodies();
+    }
+
+    /// Copies the current simulation state into each body's `position`.
+    ///
+    /// On rails, parents must come before their children in `bodies`, so that
+    /// a moon is placed relative to where its planet is this frame.
+    fn place_bodies(&mut self) {
+        match &self.physics {
+            Some(physics) => {
+                for (body, particle) in self.bodies.iter_mut().zip(physics.system.particles()) {
+                    let p = particle.position;
+                    body.position = Vec3::new(p.x as f32, p.y as f32, p.z as f32);
+                }
+            }
+            None => {
+                let positions = self.rails_positions(self.time);
+                for (body, position) in self.bodies.iter_mut().zip(positions) {
+                    body.position = position;
+                }
+            }
+        }
+    }
+
+    fn rails_positions(&self, time: f64) -> Vec<Vec3> {
+        let mut positions: Vec<Vec3> = Vec::with_capacity(self.bodies.len());
+        for body in &self.bodies {
+            let origin = match body.parent {
+                Some(parent) => positions[parent],
+                None => Vec3::zeros(),
+            };
+            let offset = match &body.orbit {
+                Some(orbit) => orbit.position_at(time),
+                None => body.local_position,
+            };
+            positions.push(origin + offset);
+        }
+        positions
+    }
+
+    fn rails_system(&self) -> NBodySystem {
+        // Central difference of the rails positions gives each body's velocity.
+        let h = 1.0 / 120.0;
+        let before = self.rails_positions(self.time - h);
+        let now = self.rails_positions(self.time);
+        let after = self.rails_positions(self.time + h);
+
+        let to_f64 = |v: Vec3| DVec3::new(v.x as f64, v.y as f64, v.z as f64);
+        let particles = self
+            .bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| Particle {
+                mass: body.mass as f64,
+                position: to_f64(now[i]),
+                velocity: to_f64(after[i] - before[i]) / (2.0 * h),
+            })
+            .collect();
+
+        let mut system = NBodySystem::new(particles, self.gravitational_constant as f64);
+        system.remove_net_momentum();
+        system
+    }
+
+    /// World-space center and radius of a sphere enclosing the body at
+    /// `index`, its ring and any vertex displacement.
+    pub fn bounding_sphere(&self, index: usize) -> (Vec3, f32) {
+        let body = &self.bodies[index];
+        let meshes = body.mesh.as_ref().unwrap_or(&self.sphere);
+        (body.position, bounding_radius(body, meshes))
+    }
+
+    /// Draws every visible body that may be in view, each with the level of
+    /// detail that suits its size on screen.
+    ///
+    /// Opaque bodies and rings are drawn first, in scene order. Translucent
+    /// ones follow from the farthest to the nearest, without writing depth,
+    /// so each blends over everything behind it.
+    ///
+    /// Every visible body, in view or not, can shadow the others.
+    pub fn render(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        uniforms: &mut Uniforms,
+        camera: &Camera,
+    ) -> FrameStats {
+        framebuffer.clear();
+
+        uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
+        uniforms.camera_position = camera.eye;
+        let frustum = Frustum::from_matrix(&(uniforms.projection_matrix * uniforms.view_matrix));
+        uniforms.shadows = self.shadows();
+
+        let mut stats = FrameStats::default();
+        let mut translucent = Vec::new();
+        for index in 0..self.bodies.len() {
+            let body = &mut self.bodies[index];
+            if !body.visible {
+                continue;
+            }
+            let meshes = body.mesh.as_ref().unwrap_or(&self.sphere);
+            if !frustum.intersects_sphere(&body.position, bounding_radius(body, meshes)) {
+                stats.culled += 1;
+                continue;
+            }
+
+            let distance = (body.position - camera.eye).magnitude();
+            body.lod = meshes.select(body.lod, projected_radius(body.scale, distance, uniforms));
+            stats.bodies += 1;
+
+            let parts = [
+                (Part::Surface, Some(body.blend_mode)),
+                (Part::Ring, body.ring.as_ref().map(|ring| ring.blend_mode)),
+                (Part::Atmosphere, body.atmosphere.as_ref().map(|_| BlendMode::Premultiplied)),
+            ];
+            for (part, blend_mode) in parts {
+                match blend_mode {
+                    Some(blend_mode) if blend_mode.is_translucent() => {
+                        translucent.push((distance, index, part));
+                    }
+                    Some(_) => {
+                        let drawn = self.draw(framebuffer, uniforms, index, part);
+                        stats.triangles += drawn.triangles;
+                        stats.coarse_rejections += drawn.coarse_rejections;
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        // Stable, so a body's ring and atmosphere sort after its surface.
+        translucent.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, index, part) in translucent {
+            let drawn = self.draw(framebuffer, uniforms, index, part);
+            stats.triangles += drawn.triangles;
+            stats.coarse_rejections += drawn.coarse_rejections;
+        }
+        framebuffer.set_blend_mode(BlendMode::Replace);
+        framebuffer.set_depth_write(true);
+        stats
+    }
+
+    /// The visible bodies as spheres that can block the Sun.
+    fn shadows(&self) -> Shadows {
+        let occluders = self
+            .bodies
+            .iter()
+            .filter(|body| body.visible)
+            .map(|body| Occluder {
+                center: body.position,
+                radius: body.mesh.as_ref().unwrap_or(&self.sphere).radius() * body.scale,
+            })
+            .collect();
+        Shadows {
+            sun_radius: self.sun_radius,
+            occluders,
+        }
+    }
+
+    /// Draws one part of the body at `index` with its blend mode, writing
+    /// depth only if it is opaque.
+    fn draw(
+        &self,
+        framebuffer: &mut Framebuffer,
+        uniforms: &mut Uniforms,
+        index: usize,
+        part: Part,
+    ) -> RenderStats {
+        let body = &self.bodies[index];
+        let meshes = body.mesh.as_ref().unwrap_or(&self.sphere);
+        // Rings use the same shader as their planet.
+        let (vertices, shader, raster_state, blend_mode): (_, &dyn FragmentShader, _, _) =
+            match (part, &body.ring, &body.atmosphere) {
+                (Part::Ring, Some(ring), _) => {
+                    uniforms.model_matrix =
+                        create_model_matrix(body.position, body.scale * ring.scale, ring.rotation);
+                    (&ring.vertex_arrays[..], body.shader.as_ref(), ring.raster_state, ring.blend_mode)
+                }
+                (Part::Atmosphere, _, Some(atmosphere)) => {
+                    let scale = body.scale * (1.0 + atmosphere.height);
+                    uniforms.model_matrix = create_model_matrix(body.position, scale, body.rotation);
+                    // The shell's vertex stage doesn't displace, so this is
+                    // a plain sphere.
+                    let vertices = meshes.mesh(body.lod);
+                    (vertices, atmosphere, RasterState::default(), BlendMode::Premultiplied)
+                }
+                _ => {
+                    uniforms.model_matrix = create_model_matrix(body.position, body.scale, body.rotation);
+                    (meshes.mesh(body.lod), body.shader.as_ref(), body.raster_state, body.blend_mode)
+                }
+            };
+        framebuffer.set_blend_mode(blend_mode);
+        framebuffer.set_depth_write(!blend_mode.is_translucent());
+        render(framebuffer, uniforms, vertices, shader, self.sun_position, raster_state)
+    }
+}
+
+/// The separately drawn parts of a body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Surface,
+    Ring,
+    Atmosphere,
+}
+
+fn bounding_radius(body: &CelestialBody, meshes: &LodMeshes) -> f32 {
+    let surface = meshes.radius() * (1.0 + body.shader.displacement_bound());
+    let ring = body.ring.as_ref().map_or(0.0, Ring::radius);
+    let atmosphere = body.atmosphere.as_ref().map_or(0.0, |atmosphere| {
+        meshes.radius() * (1.0 + atmosphere.height)
+    });
+    surface.max(ring).max(atmosphere) * body.scale
+}
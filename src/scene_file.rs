@@ -0,0 +1,432 @@
+use crate::atmosphere::Atmosphere;
+use crate::framebuffer::BlendMode;
+use crate::lod::LodMeshes;
+use crate::mesh::annulus;
+use crate::orbit::OrbitalElements;
+use crate::scene::{CelestialBody, Ring, Scene};
+use crate::shader_registry::ShaderRegistry;
+use crate::triangle::{CullMode, FrontFace, RasterState};
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    #[serde(default)]
+    sun_position: [f32; 3],
+    #[serde(default)]
+    sun_radius: f32,
+    #[serde(default)]
+    simulation: Option<SimulationDesc>,
+    #[serde(rename = "body", default)]
+    bodies: Vec<BodyDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BodyDesc {
+    name: String,
+    #[serde(default)]
+    parent: Option<String>,
+    shader: String,
+    #[serde(default)]
+    shader_params: toml::Table,
+    #[serde(default)]
+    position: Option<[f32; 3]>,
+    #[serde(default)]
+    orbit: Option<OrbitDesc>,
+    scale: f32,
+    #[serde(default)]
+    mass: f32,
+    /// XYZ rotation in degrees.
+    #[serde(default)]
+    tilt: [f32; 3],
+    #[serde(default)]
+    subdivisions: Option<u32>,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    #[serde(default)]
+    cull: Option<String>,
+    #[serde(default)]
+    front_face: Option<String>,
+    #[serde(default)]
+    blend: Option<String>,
+    #[serde(default)]
+    ring: Option<RingDesc>,
+    #[serde(default)]
+    atmosphere: Option<Atmosphere>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RingDesc {
+    #[serde(default = "default_ring_scale")]
+    scale: f32,
+    #[serde(default = "default_ring_inner_radius")]
+    inner_radius: f32,
+    #[serde(default = "default_ring_outer_radius")]
+    outer_radius: f32,
+    #[serde(default = "default_ring_segments")]
+    segments: u32,
+    /// XYZ rotation in degrees.
+    #[serde(default)]
+    tilt: [f32; 3],
+    #[serde(default)]
+    cull: Option<String>,
+    #[serde(default)]
+    front_face: Option<String>,
+    #[serde(default)]
+    blend: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SimulationDesc {
+    gravitational_constant: f32,
+}
+
+/// Orbital elements as written in scene files, with angles in degrees.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OrbitDesc {
+    semi_major_axis: f32,
+    #[serde(default)]
+    eccentricity: f32,
+    #[serde(default)]
+    inclination: f32,
+    #[serde(default)]
+    longitude_of_ascending_node: f32,
+    #[serde(default)]
+    argument_of_periapsis: f32,
+    #[serde(default)]
+    mean_anomaly_at_epoch: f32,
+    period: f32,
+}
+
+/// Applies the optional `cull` and `front_face` keys on top of `default`.
+fn parse_raster_state(
+    default: RasterState,
+    cull: Option<&str>,
+    front_face: Option<&str>,
+) -> Result<RasterState, String> {
+    let mut state = default;
+    if let Some(cull) = cull {
+        state.cull_mode = CullMode::from_name(cull).ok_or_else(|| {
+            format!("cull must be `back`, `front` or `none`, got `{}`", cull)
+        })?;
+    }
+    if let Some(front_face) = front_face {
+        state.front_face = FrontFace::from_name(front_face).ok_or_else(|| {
+            format!("front_face must be `ccw` or `cw`, got `{}`", front_face)
+        })?;
+    }
+    Ok(state)
+}
+
+/// The optional `blend` key; opaque if absent.
+fn parse_blend_mode(blend: Option<&str>) -> Result<BlendMode, String> {
+    match blend {
+        Some(blend) => BlendMode::from_name(blend).ok_or_else(|| {
+            format!(
+                "blend must be `replace`, `alpha`, `additive` or `premultiplied`, got `{}`",
+                blend
+            )
+        }),
+        None => Ok(BlendMode::Replace),
+    }
+}
+
+/// Fails unless every component of the key `name` is a finite number.
+fn require_finite(name: &str, values: &[f32]) -> Result<(), String> {
+    if values.iter().all(|value| value.is_finite()) {
+        Ok(())
+    } else {
+        Err(format!("{} must be finite, got {:?}", name, values))
+    }
+}
+
+/// Icosphere levels beyond this are too many triangles to be useful.
+const MAX_SUBDIVISIONS: u32 = 6;
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_ring_scale() -> f32 {
+    1.0
+}
+
+fn default_ring_inner_radius() -> f32 {
+    1.2
+}
+
+fn default_ring_outer_radius() -> f32 {
+    1.9
+}
+
+fn default_ring_segments() -> u32 {
+    96
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(PathBuf, std::io::Error),
+    Parse(toml::de::Error),
+    /// An invalid top-level sun setting.
+    Sun(String),
+    Simulation(String),
+    /// A body that parsed but failed validation, identified by its position
+    /// in the file and its name.
+    Body {
+        index: usize,
+        name: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            SceneError::Parse(err) => write!(f, "invalid scene file: {}", err),
+            SceneError::Sun(message) => write!(f, "{}", message),
+            SceneError::Simulation(message) => write!(f, "[simulation]: {}", message),
+            SceneError::Body {
+                index,
+                name,
+                message,
+            } => write!(f, "body #{} ({:?}): {}", index + 1, name, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Loads a scene from a TOML file. Bodies are drawn with `sphere` unless they
+/// set `subdivisions`, which gives them their own icospheres;
+/// bodies with a `[body.ring]` table get an annulus built from its radii.
+/// Shader names are looked up in `shaders`.
+pub fn load_scene(
+    path: &Path,
+    sphere: LodMeshes,
+    shaders: &ShaderRegistry,
+) -> Result<Scene, SceneError> {
+    let source =
+        fs::read_to_string(path).map_err(|err| SceneError::Io(path.to_path_buf(), err))?;
+    parse_scene(&source, sphere, shaders)
+}
+
+pub fn parse_scene(
+    source: &str,
+    sphere: LodMeshes,
+    shaders: &ShaderRegistry,
+) -> Result<Scene, SceneError> {
+    let desc: SceneDesc = toml::from_str(source).map_err(SceneError::Parse)?;
+
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut bodies = Vec::with_capacity(desc.bodies.len());
+
+    for (index, body) in desc.bodies.iter().enumerate() {
+        let error = |message: String| SceneError::Body {
+            index,
+            name: body.name.clone(),
+            message,
+        };
+
+        if body.name.is_empty() {
+            return Err(error("name must not be empty".to_string()));
+        }
+        if indices.contains_key(body.name.as_str()) {
+            return Err(error("duplicate body name".to_string()));
+        }
+
+        let shader = shaders
+            .create(&body.shader, body.shader_params.clone())
+            .map_err(error)?;
+
+        // Requiring parents to be declared first rules out cycles and lets
+        // `Scene::place_bodies` resolve positions in a single pass.
+        let parent = match &body.parent {
+            Some(parent) => Some(*indices.get(parent.as_str()).ok_or_else(|| {
+                error(format!(
+                    "parent `{}` is not declared before this body",
+                    parent
+                ))
+            })?),
+            None => None,
+        };
+
+        if !(body.scale.is_finite() && body.scale > 0.0) {
+            return Err(error(format!("scale must be positive, got {}", body.scale)));
+        }
+        if !(body.mass.is_finite() && body.mass >= 0.0) {
+            return Err(error(format!("mass must not be negative, got {}", body.mass)));
+        }
+        if let Some(position) = &body.position {
+            require_finite("position", position).map_err(error)?;
+        }
+        require_finite("tilt", &body.tilt).map_err(error)?;
+
+        let mesh = match body.subdivisions {
+            Some(subdivisions) if subdivisions > MAX_SUBDIVISIONS => {
+                return Err(error(format!(
+                    "subdivisions must be at most {}, got {}",
+                    MAX_SUBDIVISIONS, subdivisions
+                )));
+            }
+            Some(subdivisions) => Some(LodMeshes::icosphere(subdivisions)),
+            None => None,
+        };
+
+        let raster_state = parse_raster_state(
+            RasterState::default(),
+            body.cull.as_deref(),
+            body.front_face.as_deref(),
+        )
+        .map_err(error)?;
+        let blend_mode = parse_blend_mode(body.blend.as_deref()).map_err(error)?;
+
+        let ring = match &body.ring {
+            Some(ring) => {
+                if !(ring.scale.is_finite() && ring.scale > 0.0) {
+                    return Err(error(format!(
+                        "ring scale must be positive, got {}",
+                        ring.scale
+                    )));
+                }
+                if !(ring.inner_radius.is_finite()
+                    && ring.inner_radius >= 1.0
+                    && ring.outer_radius.is_finite()
+                    && ring.outer_radius > ring.inner_radius)
+                {
+                    return Err(error(format!(
+                        "ring radii must satisfy 1 <= inner_radius < outer_radius, got {} and {}",
+                        ring.inner_radius, ring.outer_radius
+                    )));
+                }
+                if !(3..=4096).contains(&ring.segments) {
+                    return Err(error(format!(
+                        "ring segments must be between 3 and 4096, got {}",
+                        ring.segments
+                    )));
+                }
+                require_finite("ring tilt", &ring.tilt).map_err(error)?;
+                let raster_state = parse_raster_state(
+                    RasterState::double_sided(),
+                    ring.cull.as_deref(),
+                    ring.front_face.as_deref(),
+                )
+                .map_err(|message| error(format!("ring {}", message)))?;
+                let blend_mode = parse_blend_mode(ring.blend.as_deref())
+                    .map_err(|message| error(format!("ring {}", message)))?;
+                Some(Ring {
+                    vertex_arrays: annulus(ring.inner_radius, ring.outer_radius, ring.segments),
+                    scale: ring.scale,
+                    rotation: Vec3::from(ring.tilt.map(f32::to_radians)),
+                    raster_state,
+                    blend_mode,
+                })
+            }
+            None => None,
+        };
+
+        if let Some(atmosphere) = &body.atmosphere {
+            atmosphere.validate().map_err(error)?;
+        }
+
+        let orbit = match &body.orbit {
+            Some(orbit) => {
+                if body.position.is_some() {
+                    return Err(error(
+                        "`position` and `orbit` cannot both be given".to_string(),
+                    ));
+                }
+                Some(validate_orbit(orbit).map_err(error)?)
+            }
+            None => None,
+        };
+
+        let local_position = Vec3::from(body.position.unwrap_or_default());
+        bodies.push(CelestialBody {
+            name: body.name.clone(),
+            parent,
+            local_position,
+            orbit,
+            mass: body.mass,
+            position: local_position,
+            scale: body.scale,
+            rotation: Vec3::from(body.tilt.map(f32::to_radians)),
+            mesh,
+            lod: 0,
+            shader,
+            raster_state,
+            blend_mode,
+            visible: body.visible,
+            ring,
+            atmosphere: body.atmosphere.clone(),
+        });
+        indices.insert(&body.name, index);
+    }
+
+    require_finite("sun_position", &desc.sun_position).map_err(SceneError::Sun)?;
+    if !(desc.sun_radius.is_finite() && desc.sun_radius >= 0.0) {
+        return Err(SceneError::Sun(format!(
+            "sun_radius must not be negative, got {}",
+            desc.sun_radius
+        )));
+    }
+    let mut scene = Scene::new(bodies, sphere, Vec3::from(desc.sun_position));
+    scene.sun_radius = desc.sun_radius;
+    if let Some(simulation) = desc.simulation {
+        if !(simulation.gravitational_constant.is_finite()
+            && simulation.gravitational_constant > 0.0)
+        {
+            return Err(SceneError::Simulation(format!(
+                "gravitational_constant must be positive, got {}",
+                simulation.gravitational_constant
+            )));
+        }
+        scene.gravitational_constant = simulation.gravitational_constant;
+    }
+    Ok(scene)
+}
+
+fn validate_orbit(orbit: &OrbitDesc) -> Result<OrbitalElements, String> {
+    if !(orbit.semi_major_axis.is_finite() && orbit.semi_major_axis > 0.0) {
+        return Err(format!(
+            "orbit semi_major_axis must be positive, got {}",
+            orbit.semi_major_axis
+        ));
+    }
+    if !(0.0..1.0).contains(&orbit.eccentricity) {
+        return Err(format!(
+            "orbit eccentricity must be in [0, 1), got {}",
+            orbit.eccentricity
+        ));
+    }
+    if !(orbit.period.is_finite() && orbit.period > 0.0) {
+        return Err(format!("orbit period must be positive, got {}", orbit.period));
+    }
+    for (name, angle) in [
+        ("orbit inclination", orbit.inclination),
+        ("orbit longitude_of_ascending_node", orbit.longitude_of_ascending_node),
+        ("orbit argument_of_periapsis", orbit.argument_of_periapsis),
+        ("orbit mean_anomaly_at_epoch", orbit.mean_anomaly_at_epoch),
+    ] {
+        require_finite(name, &[angle])?;
+    }
+
+    Ok(OrbitalElements {
+        semi_major_axis: orbit.semi_major_axis,
+        eccentricity: orbit.eccentricity,
+        inclination: orbit.inclination.to_radians(),
+        longitude_of_ascending_node: orbit.longitude_of_ascending_node.to_radians(),
+        argument_of_periapsis: orbit.argument_of_periapsis.to_radians(),
+        mean_anomaly_at_epoch: orbit.mean_anomaly_at_epoch.to_radians(),
+        period: orbit.period,
+    })
+}
@@ -0,0 +1,111 @@
+use crate::procedural::{PlanetClass, PlanetParams, ProceduralPlanetShader};
+use crate::shaders::{
+    EarthShader, FragmentShader, GasGiantShader, JupiterShader, MarsShader, MercuryShader,
+    MoonShader, RockyPlanetShader, SaturnShader, StarShader, VenusShader,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Builds a shader from the `shader_params` table of a scene file body.
+pub type ShaderFactory =
+    Box<dyn Fn(toml::Table) -> Result<Box<dyn FragmentShader>, String> + Send + Sync>;
+
+/// `shader_params` of the `procedural` shader.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProceduralDesc {
+    class: String,
+    seed: u64,
+}
+
+/// Shaders available to scene files, by the name used in a body's `shader`
+/// key.
+///
+/// `ShaderRegistry::default()` holds the built-in looks; applications add
+/// their own with [`register`](Self::register) or
+/// [`register_params`](Self::register_params) before loading a scene.
+pub struct ShaderRegistry {
+    factories: HashMap<String, ShaderFactory>,
+}
+
+impl ShaderRegistry {
+    /// A registry with no shaders at all.
+    pub fn empty() -> Self {
+        ShaderRegistry {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Registers `factory` under `name`, replacing any shader already there.
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: impl Fn(toml::Table) -> Result<Box<dyn FragmentShader>, String>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Registers a shader whose parameters are deserialized straight from
+    /// `shader_params`. Missing parameters take the type's defaults when it
+    /// uses `#[serde(default)]`, as the built-in shaders do.
+    pub fn register_params<S>(&mut self, name: &str)
+    where
+        S: FragmentShader + DeserializeOwned + 'static,
+    {
+        self.register(name, |params| {
+            let shader: S = toml::Value::Table(params)
+                .try_into()
+                .map_err(|err| format!("invalid shader_params: {}", err))?;
+            Ok(Box::new(shader))
+        });
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Registered names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn create(&self, name: &str, params: toml::Table) -> Result<Box<dyn FragmentShader>, String> {
+        let factory = self
+            .factories
+            .get(name)
+            .ok_or_else(|| format!("unknown shader `{}`", name))?;
+        factory(params)
+    }
+}
+
+impl Default for ShaderRegistry {
+    fn default() -> Self {
+        let mut registry = ShaderRegistry::empty();
+        registry.register_params::<StarShader>("star");
+        registry.register_params::<MercuryShader>("mercury");
+        registry.register_params::<VenusShader>("venus");
+        registry.register_params::<EarthShader>("earth");
+        registry.register_params::<MarsShader>("mars");
+        registry.register_params::<JupiterShader>("jupiter");
+        registry.register_params::<SaturnShader>("saturn");
+        registry.register_params::<MoonShader>("moon");
+        registry.register_params::<RockyPlanetShader>("rocky_planet");
+        registry.register_params::<GasGiantShader>("gas_giant");
+        registry.register("procedural", |params| {
+            let desc: ProceduralDesc = toml::Value::Table(params)
+                .try_into()
+                .map_err(|err| format!("invalid shader_params: {}", err))?;
+            let class = PlanetClass::from_name(&desc.class)
+                .ok_or_else(|| format!("unknown planet class `{}`", desc.class))?;
+            let params = PlanetParams::generate(class, desc.seed);
+            Ok(Box::new(ProceduralPlanetShader::new(params)))
+        });
+        registry
+    }
+}
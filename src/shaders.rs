@@ -1,15 +1,35 @@
 use crate::color::Color;
 use crate::fragment::Fragment;
+use crate::noise::{fbm, ridged, sample, sphere_point, surface_point, warped_fbm, Fractal};
 use crate::vertex::Vertex;
 use crate::Uniforms;
-use fastnoise_lite::{FastNoiseLite, NoiseType};
+use fastnoise_lite::FastNoiseLite;
 use nalgebra_glm::{mat4_to_mat3, Mat3, Vec3, Vec4};
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+/// How a body's surface is drawn.
+///
+/// Shaders are shared between render threads, so they must be `Send + Sync`.
+/// Parameters live on the implementing type; see
+/// [`ShaderRegistry`](crate::shader_registry::ShaderRegistry) for making a
+/// shader available to scene files.
+pub trait FragmentShader: Send + Sync {
+    /// Vertex stage. The default applies the model, view and projection
+    /// transforms with [`vertex_shader`].
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        vertex_shader(vertex, uniforms)
+    }
+
+    /// How far the vertex stage may move a vertex outwards, as a fraction of
+    /// its distance from the body's center. Bodies are culled by a bounding
+    /// sphere grown by this much.
+    fn displacement_bound(&self) -> f32 {
+        0.0
+    }
 
-fn create_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::Perlin));
-    noise.set_frequency(Some(0.05));
-    noise
+    /// Color of one fragment, lit by a sun at `sun_position`.
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color;
 }
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
@@ -17,18 +37,9 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 
     let world_position = uniforms.model_matrix * position;
 
+    // The perspective divide happens after clipping, in `render`.
     let clip_position = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
-    let w = clip_position.w;
-    let ndc_position = Vec4::new(
-        clip_position.x / w,
-        clip_position.y / w,
-        clip_position.z / w,
-        1.0,
-    );
-
-    let screen_position = uniforms.viewport_matrix * ndc_position;
-
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
     let normal_matrix = model_mat3
         .transpose()
@@ -41,384 +52,632 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         normal: vertex.normal,
         tex_coords: vertex.tex_coords,
         color: vertex.color,
-        transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+        transformed_position: Vec3::new(clip_position.x, clip_position.y, clip_position.z),
         transformed_normal,
         world_position: Vec3::new(world_position.x, world_position.y, world_position.z),
+        clip_position,
     }
 }
 
-pub fn planet_fragment_shader(
-    fragment: &Fragment,
-    uniforms: &Uniforms,
-    planet_type: &str,
-    sun_position: Vec3,
-) -> Color {
-    match planet_type {
-        "Sun" => star_fragment_shader(fragment, uniforms),
-        "Mercury" => mercury_shader(fragment, uniforms, sun_position),
-        "Venus" => venus_shader(fragment, uniforms, sun_position),
-        "Earth" => earth_shader(fragment, uniforms, sun_position),
-        "Mars" => mars_shader(fragment, uniforms, sun_position),
-        "Jupiter" => jupiter_shader(fragment, uniforms, sun_position),
-        "Saturn" => saturn_shader(fragment, uniforms, sun_position),
-        "Moon" => moon_shader(fragment, uniforms, sun_position),
-        _ => default_shader(fragment, uniforms, sun_position),
-    }
-}
-
-pub fn star_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Slower pulsing effect with increased base brightness
-    let time_factor = 0.95 + 0.15 * ((uniforms.time as f32 * 0.02).sin());
-    
-    // Add noise for surface detail
-    let zoom = 30.0;
-    let surface_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom + uniforms.time as f32 * 0.01,
-        fragment.tex_coords.y * zoom
-    );
-    
-    // Add plasma-like effect
-    let plasma_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 0.5 - uniforms.time as f32 * 0.015,
-        fragment.tex_coords.y * zoom * 0.5 + uniforms.time as f32 * 0.015
-    );
-
-    // Increased base values for more brightness
-    let red = 1.2;  // Increased from 1.0
-    let green = 0.9 + 0.2 * surface_noise;  // Increased base from 0.84
-    let blue = 0.3 + 0.15 * plasma_noise;   // Increased both base and variation
-
-    let gradient = (1.0 - fragment.world_position.magnitude() * 0.04).max(0.0);  // Reduced falloff
-    let noise_factor = 0.9 + 0.2 * (surface_noise + plasma_noise);  // Increased variation
-    
-    Color::from_float(
-        (red * gradient * time_factor * noise_factor).min(1.0),
-        (green * gradient * time_factor * noise_factor).min(1.0),
-        (blue * gradient * time_factor * noise_factor).min(1.0),
-    )
-}
-
-pub fn mercury_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let zoom = 60.0;
-    let time = uniforms.time as f32 * 0.02;
-    
-    // Create swirling patterns for the purple surface
-    let surface_pattern = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom + time,
-        fragment.tex_coords.y * zoom - time * 0.5
-    );
-    
-    // Create crystalline/mineral effects
-    let crystal_pattern = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 2.0 - time * 0.8,
-        fragment.tex_coords.y * zoom * 2.0 + time * 0.3
-    );
-
-    // Deep purple surface with crystalline variations
-    let surface = Color::from_float(
-        0.5 + 0.2 * crystal_pattern,  // Purple-red component
-        0.2 + 0.1 * surface_pattern,  // Minimal green for depth
-        0.8 + 0.2 * crystal_pattern   // Strong blue for purple tint
-    );
-
-    apply_enhanced_lighting(fragment, uniforms, sun_position, surface, 1.4)
-}
-
-pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let zoom = 70.0;
-    let time = uniforms.time as f32 * 0.015;
-    
-    // Create dense sulfuric cloud patterns
-    let cloud_pattern = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom + time,
-        fragment.tex_coords.y * zoom - time * 0.7
-    );
-    
-    // Create turbulent atmospheric flows
-    let turbulence = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 1.5 - time * 0.5,
-        fragment.tex_coords.y * zoom * 1.5 + time * 0.3
-    );
-    
-    // Add heat distortion effect
-    let heat_pattern = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 2.0 + time * 0.2,
-        fragment.tex_coords.y * zoom * 2.0 - time * 0.2
-    );
-
-    // Golden-orange sulfuric atmosphere with variations
-    let atmosphere = Color::from_float(
-        0.85 + 0.15 * turbulence,     // Strong golden-red
-        0.65 + 0.15 * cloud_pattern,  // Medium orange
-        0.2 + 0.1 * heat_pattern     // Slight yellow tint
-    );
-
-    // Use lower intensity for more saturated colors
-    apply_enhanced_lighting(fragment, uniforms, sun_position, atmosphere, 1.3)
-}
-
-pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let zoom = 80.0;
-    let noise_value = uniforms
-        .noise
-        .get_noise_2d(fragment.tex_coords.x * zoom, fragment.tex_coords.y * zoom);
-    let mountain_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 2.0,
-        fragment.tex_coords.y * zoom * 2.0,
-    );
-
-    let water_color = Color::from_float(0.1, 0.5, 1.0);  // Made water more vibrant
-    let land_color = Color::from_float(0.2, 0.9, 0.2);   // Made land more vibrant
-    let mountain_color = Color::from_float(0.8, 0.6, 0.5); // Brighter mountains
-
-    let base_color = if noise_value > 0.6 {
-        mountain_color * (1.0 + mountain_noise * 0.5)
-    } else if noise_value > 0.2 {
-        land_color
-    } else {
-        water_color
+/// Raises `vertex` by `relief * height(direction)` along its direction from
+/// the body's center, in units of its distance from the center, and
+/// recomputes its normal from the raised surface. Shaders with terrain call
+/// this before [`vertex_shader`] in their vertex stage, passing the same
+/// heightfield their fragment stage colors by.
+pub fn displace(vertex: &Vertex, relief: f32, height: impl Fn(Vec3) -> f32) -> Vertex {
+    let direction = match vertex.position.try_normalize(f32::EPSILON) {
+        Some(direction) if relief != 0.0 => direction,
+        _ => return vertex.clone(),
     };
+    let radius = vertex.position.magnitude();
+    let surface = |d: Vec3| d * radius * (1.0 + relief * height(d));
+
+    // The normal of the raised surface, from finite differences along two
+    // tangents.
+    const STEP: f32 = 1e-3;
+    let up = if direction.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+    let tangent = up.cross(&direction).normalize();
+    let bitangent = direction.cross(&tangent);
+    let position = surface(direction);
+    let along_tangent = surface((direction + tangent * STEP).normalize()) - position;
+    let along_bitangent = surface((direction + bitangent * STEP).normalize()) - position;
+    let normal = along_tangent
+        .cross(&along_bitangent)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(direction);
 
-    // Enhanced cloud and atmosphere effects
-    let cloud_zoom = 30.0;
-    let cloud_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * cloud_zoom + uniforms.time as f32 * 0.01,
-        fragment.tex_coords.y * cloud_zoom + uniforms.time as f32 * 0.01,
-    );
-    let cloud_alpha = (cloud_noise * 0.5 + 0.5).clamp(0.0, 1.0);
-    let cloud_color = Color::from_float(1.2, 1.2, 1.2);
-
-    let atmosphere_factor = (1.0 - fragment.normal.dot(&Vec3::new(0.0, 1.0, 0.0))).powi(2);
-    let atmosphere_color = Color::from_float(0.6, 0.8, 1.2);
-    let final_color =
-        base_color * (1.0 - atmosphere_factor) + atmosphere_color * atmosphere_factor * 0.4;
-
-    let mixed_color = final_color * (1.0 - cloud_alpha) + cloud_color * cloud_alpha;
-
-    apply_enhanced_lighting(fragment, uniforms, sun_position, mixed_color, 1.8)
-}
-
-pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let zoom = 50.0;
-    let time = uniforms.time as f32 * 0.02;
-    
-    // Create base rocky terrain
-    let rock_pattern = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 2.0,
-        fragment.tex_coords.y * zoom * 2.0
-    ).abs();
-    
-    // Add larger rock formations
-    let large_rocks = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom,
-        fragment.tex_coords.y * zoom
-    ).abs();
-    
-    // Create canyons and valleys
-    let canyons = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 3.0,
-        fragment.tex_coords.y * zoom * 3.0
-    ).abs();
-    
-    // Dynamic dust storms with time variation
-    let dust_storm = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom + time,
-        fragment.tex_coords.y * zoom - time
-    );
-
-    // Combine different terrain features
-    let terrain = (rock_pattern * 0.4 + large_rocks * 0.4 + canyons * 0.2)
-        .max(0.0)
-        .min(1.0);
-
-    // Create color variations for different terrain features
-    let base_red = 0.8 + 0.2 * terrain;  // Brighter red for highlands
-    let base_brown = 0.3 + 0.2 * large_rocks; // Brown variations for rocks
-    let dust_color = 0.1 + 0.1 * dust_storm;  // Subtle dust effect
-
-    let base_color = Color::from_float(
-        base_red,     // Strong red base
-        base_brown,   // Brown/orange mix
-        dust_color    // Dust influence
-    );
-
-    // Apply lighting with enhanced shadows for rocky appearance
-    apply_enhanced_lighting(fragment, uniforms, sun_position, base_color, 1.4)
-}
-
-pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let time = uniforms.time as f32 * 0.02;
-    let latitude = fragment.tex_coords.y * PI;
-    
-    // Enhanced band patterns
-    let band_pattern = (latitude * 12.0).sin() * 0.5 + 0.5;
-    let secondary_bands = (latitude * 20.0).sin() * 0.3;
-    
-    // Dynamic storm patterns
-    let storm = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * 30.0 + time,
-        fragment.tex_coords.y * 30.0
-    );
-
-    let base_color = Color::from_float(
-        0.9 + 0.3 * band_pattern,           // Warm orange-brown
-        0.7 + 0.2 * band_pattern + secondary_bands,  // Varied yellows
-        0.4 + 0.4 * storm                   // Storm highlights
-    );
-
-    apply_enhanced_lighting(fragment, uniforms, sun_position, base_color, 1.7)
-}
-
-pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let time = uniforms.time as f32 * 0.01;
-    let zoom = 60.0;
-    
-    // Calculate ring parameters based on world position
-    let ring_y = fragment.world_position.y.abs();  // Use absolute Y for ring detection
-    let ring_distance = (fragment.world_position.x.powi(2) + fragment.world_position.z.powi(2)).sqrt();
-    
-    // Ring parameters
-    let inner_radius = 1.2;
-    let outer_radius = 2.5;
-    let ring_thickness = 0.15;  // Maximum thickness of rings
-    
-    // Determine if we're in the ring region
-    let is_ring = if ring_distance > inner_radius && ring_distance < outer_radius && ring_y < ring_thickness {
-        // Calculate ring intensity based on distance from center and y-position
-        let distance_factor = 1.0 - ((ring_distance - inner_radius) / (outer_radius - inner_radius));
-        let height_factor = 1.0 - (ring_y / ring_thickness);
-        (distance_factor * height_factor).max(0.0)
-    } else {
-        0.0
-    };
+    Vertex {
+        position,
+        normal: if normal.dot(&direction) < 0.0 { -normal } else { normal },
+        ..vertex.clone()
+    }
+}
 
-    // Create base planet color
-    let base_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom,
-        fragment.tex_coords.y * zoom
-    );
-    
-    let surface_color = Color::from_float(
-        0.9 + 0.1 * base_noise,    // Golden tone
-        0.7 + 0.2 * base_noise,    // Warm yellow
-        0.5 + 0.1 * base_noise     // Less blue
-    );
-
-    // Create ring bands pattern
-    let ring_pattern = (ring_distance * 8.0).sin() * 0.5 + 0.5;  // Create circular bands
-    let ring_noise = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * 120.0 + time,
-        fragment.tex_coords.y * 120.0
-    ) * 0.3;
-
-    // Enhanced ring color with more contrast and variation
-    let ring_color = Color::from_float(
-        1.0 * (0.8 + 0.2 * ring_pattern + ring_noise),  // Brighter base
-        0.95 * (0.7 + 0.3 * ring_pattern + ring_noise), // Slight golden tint
-        0.9 * (0.6 + 0.4 * ring_pattern + ring_noise)   // Warmer tone
-    );
-
-    // Mix planet and ring colors with enhanced contrast
-    let final_color = if is_ring > 0.0 {
-        let ring_intensity = is_ring * (0.8 + 0.2 * ring_pattern);  // Vary ring intensity
-        Color::from_float(
-            ring_color.r as f32 / 255.0 * ring_intensity,
-            ring_color.g as f32 / 255.0 * ring_intensity,
-            ring_color.b as f32 / 255.0 * ring_intensity
-        )
-    } else {
-        surface_color
-    };
+/// Self-lit plasma surface with a slow pulse.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StarShader {
+    pub zoom: f32,
+    pub color: [f32; 3],
+    /// Emitted light relative to `color`. Well above the lit planets, so
+    /// the star is what blooms.
+    pub brightness: f32,
+}
+
+impl Default for StarShader {
+    fn default() -> Self {
+        StarShader {
+            zoom: 30.0,
+            color: [1.2, 0.9, 0.3],
+            brightness: 6.0,
+        }
+    }
+}
+
+impl FragmentShader for StarShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, _sun_position: Vec3) -> Color {
+        // Slower pulsing effect with increased base brightness
+        let time_factor = 0.95 + 0.15 * ((uniforms.time * 1.2).sin());
+
+        // Add noise for surface detail
+        let p = surface_point(fragment, self.zoom);
+        let surface_noise = sample(&uniforms.noise, p + Vec3::new(uniforms.time * 0.6, 0.0, 0.0));
+
+        // Add plasma-like effect
+        let plasma_noise = sample(
+            &uniforms.noise,
+            p * 0.5 + Vec3::new(-uniforms.time * 0.9, uniforms.time * 0.9, 0.0),
+        );
+
+        let [red, green, blue] = self.color;
+        let green = green + 0.2 * surface_noise;
+        let blue = blue + 0.15 * plasma_noise;
+
+        let gradient = (1.0 - fragment.object_position.magnitude() * 0.04).max(0.0);  // Reduced falloff
+        let noise_factor = 0.9 + 0.2 * (surface_noise + plasma_noise);  // Increased variation
+
+        // Brighter than 1 where it burns hottest; tone mapping keeps the hue.
+        color([red, green, blue]) * (self.brightness * gradient * time_factor * noise_factor)
+    }
+}
+
+/// Purple crystalline surface.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MercuryShader {
+    pub zoom: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Default for MercuryShader {
+    fn default() -> Self {
+        MercuryShader {
+            zoom: 60.0,
+            color: [0.5, 0.2, 0.8],
+            intensity: 1.4,
+        }
+    }
+}
+
+impl FragmentShader for MercuryShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let p = surface_point(fragment, self.zoom);
+        let time = uniforms.time * 1.2;
+
+        // Create swirling patterns for the purple surface
+        let surface_pattern = sample(&uniforms.noise, p + Vec3::new(time, -time * 0.5, 0.0));
+
+        // Create crystalline/mineral effects
+        let crystal_pattern =
+            sample(&uniforms.noise, p * 2.0 + Vec3::new(-time * 0.8, time * 0.3, 0.0));
+
+        // Deep purple surface with crystalline variations
+        let [r, g, b] = self.color;
+        let surface = Color::from_srgb(
+            r + 0.2 * crystal_pattern,  // Purple-red component
+            g + 0.1 * surface_pattern,  // Minimal green for depth
+            b + 0.2 * crystal_pattern   // Strong blue for purple tint
+        );
+
+        apply_enhanced_lighting(fragment, uniforms, sun_position, surface, self.intensity)
+    }
+}
 
-    apply_enhanced_lighting(fragment, uniforms, sun_position, final_color, 2.0)
-}
-
-pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let zoom = 40.0;
-    
-    // Create large crater effects
-    let large_craters = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom,
-        fragment.tex_coords.y * zoom,
-    ).abs();
-    
-    // Create smaller, more numerous craters
-    let small_craters = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 4.0,
-        fragment.tex_coords.y * zoom * 4.0,
-    ).abs();
-    
-    // Create surface texture variations
-    let surface_texture = uniforms.noise.get_noise_2d(
-        fragment.tex_coords.x * zoom * 2.0,
-        fragment.tex_coords.y * zoom * 2.0,
-    );
-
-    // Combine crater effects
-    let crater_depth = (large_craters * 0.7 + small_craters * 0.3)
-        .max(0.0)
-        .min(1.0);
-
-    // Create mare (dark areas) effect
-    let mare_effect = surface_texture.abs() * 0.3;
-
-    // Base colors for light and dark areas
-    let light_color = Color::from_float(0.8, 0.8, 0.85);  // Slightly bluish white
-    let dark_color = Color::from_float(0.3, 0.3, 0.35);   // Dark gray
-    let mare_color = Color::from_float(0.2, 0.2, 0.25);   // Darker gray for maria
-
-    // Mix colors based on crater depth and mare
-    let mixed_color = if mare_effect > 0.2 {
-        mare_color
-    } else {
-        let crater_factor = 1.0 - crater_depth * 0.5;
-        Color::from_float(
-            light_color.r as f32 / 255.0 * crater_factor,
-            light_color.g as f32 / 255.0 * crater_factor,
-            light_color.b as f32 / 255.0 * crater_factor
+/// Dense, turbulent sulfuric clouds.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VenusShader {
+    pub zoom: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Default for VenusShader {
+    fn default() -> Self {
+        VenusShader {
+            zoom: 70.0,
+            color: [0.85, 0.65, 0.2],
+            intensity: 1.3,
+        }
+    }
+}
+
+impl FragmentShader for VenusShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let p = surface_point(fragment, self.zoom);
+        let time = uniforms.time * 0.9;
+
+        // Create dense sulfuric cloud patterns
+        let cloud_pattern = sample(&uniforms.noise, p + Vec3::new(time, -time * 0.7, 0.0));
+
+        // Create turbulent atmospheric flows
+        let turbulence = warped_fbm(
+            &uniforms.noise,
+            p * 1.5 + Vec3::new(-time * 0.5, time * 0.3, 0.0),
+            2.0,
+            Fractal::octaves(3),
+        );
+
+        // Add heat distortion effect
+        let heat_pattern =
+            sample(&uniforms.noise, p * 2.0 + Vec3::new(time * 0.2, -time * 0.2, 0.0));
+
+        // Golden-orange sulfuric atmosphere with variations
+        let [r, g, b] = self.color;
+        let atmosphere = Color::from_srgb(
+            r + 0.15 * turbulence,     // Strong golden-red
+            g + 0.15 * cloud_pattern,  // Medium orange
+            b + 0.1 * heat_pattern     // Slight yellow tint
+        );
+
+        // Use lower intensity for more saturated colors
+        apply_enhanced_lighting(fragment, uniforms, sun_position, atmosphere, self.intensity)
+    }
+}
+
+/// Oceans, continents and mountains under moving clouds.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EarthShader {
+    pub zoom: f32,
+    pub cloud_zoom: f32,
+    pub water_color: [f32; 3],
+    pub land_color: [f32; 3],
+    pub mountain_color: [f32; 3],
+    pub intensity: f32,
+    /// Height of the tallest mountains as a fraction of the radius; 0 keeps
+    /// the sphere smooth.
+    pub relief: f32,
+}
+
+impl Default for EarthShader {
+    fn default() -> Self {
+        EarthShader {
+            zoom: 80.0,
+            cloud_zoom: 30.0,
+            water_color: [0.1, 0.5, 1.0],
+            land_color: [0.2, 0.9, 0.2],
+            mountain_color: [0.8, 0.6, 0.5],
+            intensity: 1.8,
+            relief: 0.03,
+        }
+    }
+}
+
+impl EarthShader {
+    /// Continent noise and ridged mountain detail at `position`.
+    fn terrain(&self, position: Vec3, noise: &FastNoiseLite) -> (f32, f32) {
+        let p = sphere_point(position, self.zoom);
+        let continent = sample(noise, p);
+        let mountain = ridged(noise, p * 2.0, Fractal::octaves(3)) * 2.0 - 1.0;
+        (continent, mountain)
+    }
+
+    /// Height above sea level; oceans are flat.
+    fn height(&self, position: Vec3, noise: &FastNoiseLite) -> f32 {
+        let (continent, mountain) = self.terrain(position, noise);
+        let land = ((continent - 0.2) / 0.8).max(0.0);
+        let peaks = ((continent - 0.6) / 0.4).max(0.0) * (mountain * 0.5 + 0.5);
+        (land + peaks) / 2.0
+    }
+}
+
+impl FragmentShader for EarthShader {
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        let raised = displace(vertex, self.relief, |d| self.height(d, &uniforms.noise));
+        vertex_shader(&raised, uniforms)
+    }
+
+    fn displacement_bound(&self) -> f32 {
+        self.relief.abs()
+    }
+
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let (noise_value, mountain_noise) = self.terrain(fragment.object_position, &uniforms.noise);
+
+        let water_color = color(self.water_color);
+        let land_color = color(self.land_color);
+        let mountain_color = color(self.mountain_color);
+
+        let base_color = if noise_value > 0.6 {
+            mountain_color * (1.0 + mountain_noise * 0.5)
+        } else if noise_value > 0.2 {
+            land_color
+        } else {
+            water_color
+        };
+
+        // Enhanced cloud and atmosphere effects
+        let cloud_drift = Vec3::new(uniforms.time * 0.6, uniforms.time * 0.6, 0.0);
+        let cloud_noise = fbm(
+            &uniforms.noise,
+            surface_point(fragment, self.cloud_zoom) + cloud_drift,
+            Fractal::octaves(3),
+        );
+        let cloud_alpha = (cloud_noise * 0.5 + 0.5).clamp(0.0, 1.0);
+        let cloud_color = Color::from_float(1.2, 1.2, 1.2);
+
+        // The sky itself comes from the body's atmosphere shell.
+        let mixed_color = base_color * (1.0 - cloud_alpha) + cloud_color * cloud_alpha;
+
+        apply_enhanced_lighting(fragment, uniforms, sun_position, mixed_color, self.intensity)
+    }
+}
+
+/// Red rocky terrain with canyons and drifting dust storms.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarsShader {
+    pub zoom: f32,
+    pub intensity: f32,
+    /// Depth of the canyons plus height of the rocks, as a fraction of the
+    /// radius; 0 keeps the sphere smooth.
+    pub relief: f32,
+}
+
+impl Default for MarsShader {
+    fn default() -> Self {
+        MarsShader {
+            zoom: 50.0,
+            intensity: 1.4,
+            relief: 0.04,
+        }
+    }
+}
+
+impl MarsShader {
+    /// Small rocks, large rock formations and canyon crests at `position`.
+    fn terrain(&self, position: Vec3, noise: &FastNoiseLite) -> (f32, f32, f32) {
+        let p = sphere_point(position, self.zoom);
+        (
+            sample(noise, p * 2.0).abs(),
+            sample(noise, p).abs(),
+            ridged(noise, p * 3.0, Fractal::octaves(3)),
         )
-    };
+    }
+
+    /// Rock formations stand up and canyons cut down along the ridge lines.
+    fn height(&self, position: Vec3, noise: &FastNoiseLite) -> f32 {
+        let (rock_pattern, large_rocks, canyons) = self.terrain(position, noise);
+        large_rocks * 0.5 + rock_pattern * 0.2 - canyons.powi(4) * 0.5
+    }
+}
+
+impl FragmentShader for MarsShader {
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        let raised = displace(vertex, self.relief, |d| self.height(d, &uniforms.noise));
+        vertex_shader(&raised, uniforms)
+    }
+
+    fn displacement_bound(&self) -> f32 {
+        self.relief.abs()
+    }
+
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let p = surface_point(fragment, self.zoom);
+        let time = uniforms.time * 1.2;
+
+        // Rocky terrain, rock formations, and canyons and valleys
+        let (rock_pattern, large_rocks, canyons) =
+            self.terrain(fragment.object_position, &uniforms.noise);
 
-    // Apply enhanced lighting with reduced intensity for more contrast
-    apply_enhanced_lighting(fragment, uniforms, sun_position, mixed_color, 1.2)
+        // Dynamic dust storms with time variation
+        let dust_storm = sample(&uniforms.noise, p + Vec3::new(time, -time, 0.0));
+
+        // Combine different terrain features
+        let terrain = (rock_pattern * 0.4 + large_rocks * 0.4 + canyons * 0.2)
+            .clamp(0.0, 1.0);
+
+        // Create color variations for different terrain features
+        let base_red = 0.8 + 0.2 * terrain;  // Brighter red for highlands
+        let base_brown = 0.3 + 0.2 * large_rocks; // Brown variations for rocks
+        let dust_color = 0.1 + 0.1 * dust_storm;  // Subtle dust effect
+
+        let base_color = Color::from_srgb(
+            base_red,     // Strong red base
+            base_brown,   // Brown/orange mix
+            dust_color    // Dust influence
+        );
+
+        // Apply lighting with enhanced shadows for rocky appearance
+        apply_enhanced_lighting(fragment, uniforms, sun_position, base_color, self.intensity)
+    }
 }
 
-pub fn default_shader(fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
-    let base_color = Color::new(100, 100, 100);
-    apply_lighting(fragment, uniforms, sun_position, base_color)
+/// Banded gas giant with storms.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JupiterShader {
+    /// Frequency of the main bands from pole to pole.
+    pub bands: f32,
+    pub secondary_bands: f32,
+    pub storm_zoom: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
 }
 
-fn apply_lighting(
-    fragment: &Fragment,
-    uniforms: &Uniforms,
-    sun_position: Vec3,
-    base_color: Color,
-) -> Color {
-    let light_dir = (sun_position - fragment.world_position).normalize();
-    let diffuse = fragment.normal.dot(&light_dir).max(0.0);
-    let diffuse_intensity = 1.5 * diffuse;
+impl Default for JupiterShader {
+    fn default() -> Self {
+        JupiterShader {
+            bands: 12.0,
+            secondary_bands: 20.0,
+            storm_zoom: 30.0,
+            color: [0.9, 0.7, 0.4],
+            intensity: 1.7,
+        }
+    }
+}
 
-    let view_dir = (-fragment.world_position).normalize();
-    let reflect_dir =
-        (2.0 * fragment.normal.dot(&light_dir) * fragment.normal - light_dir).normalize();
-    let specular = reflect_dir.dot(&view_dir).max(0.0).powi(16);
-    let specular_intensity = 0.3 * specular;
+impl FragmentShader for JupiterShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let time = uniforms.time * 1.2;
+        let p = surface_point(fragment, self.storm_zoom);
+        // Polar angle, 0 at the north pole and π at the south pole.
+        let latitude = (p.y / p.magnitude().max(f32::EPSILON)).clamp(-1.0, 1.0).acos();
 
-    let distance_to_sun = (sun_position - fragment.world_position).magnitude();
-    let attenuation = 1.0 / (1.0 + 0.005 * distance_to_sun * distance_to_sun);
+        // Enhanced band patterns
+        let band_pattern = (latitude * self.bands).sin() * 0.5 + 0.5;
+        let secondary_bands = (latitude * self.secondary_bands).sin() * 0.3;
+
+        // Dynamic storm patterns
+        let storm = sample(&uniforms.noise, p + Vec3::new(time, 0.0, 0.0));
+
+        let [r, g, b] = self.color;
+        let base_color = Color::from_srgb(
+            r + 0.3 * band_pattern,           // Warm orange-brown
+            g + 0.2 * band_pattern + secondary_bands,  // Varied yellows
+            b + 0.4 * storm                   // Storm highlights
+        );
+
+        apply_enhanced_lighting(fragment, uniforms, sun_position, base_color, self.intensity)
+    }
+}
+
+/// Golden gas giant; fragments from a flat ring mesh outside the planet get
+/// banded ring colors, so the body's ring can share this shader.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SaturnShader {
+    pub zoom: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Default for SaturnShader {
+    fn default() -> Self {
+        SaturnShader {
+            zoom: 60.0,
+            color: [0.9, 0.7, 0.5],
+            intensity: 2.0,
+        }
+    }
+}
+
+impl FragmentShader for SaturnShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let time = uniforms.time * 0.6;
+        let zoom = self.zoom;
+
+        // Work in the planet's own space, so the rings follow Saturn wherever
+        // it is. Ring meshes lie flat in the equatorial plane, outside the
+        // unit sphere.
+        let ring_y = fragment.object_position.y.abs();
+        let ring_distance = (fragment.object_position.x.powi(2) + fragment.object_position.z.powi(2)).sqrt();
+
+        let is_ring = if ring_y < 1e-4 && ring_distance > 1.0 + 1e-3 {
+            // Rings fade towards their outer edge, where `v` reaches 1
+            1.0 - fragment.tex_coords.y * 0.5
+        } else {
+            0.0
+        };
+
+        // Create base planet color
+        let base_noise = sample(&uniforms.noise, surface_point(fragment, zoom));
+
+        let [r, g, b] = self.color;
+        let surface_color = Color::from_srgb(
+            r + 0.1 * base_noise,    // Golden tone
+            g + 0.2 * base_noise,    // Warm yellow
+            b + 0.1 * base_noise     // Less blue
+        );
+
+        // Create ring bands pattern
+        let ring_pattern = (ring_distance * 8.0).sin() * 0.5 + 0.5;  // Create circular bands
+        // Ring fragments lie at different radii, so sample the position
+        // itself rather than its direction
+        let ring_noise = sample(
+            &uniforms.noise,
+            fragment.object_position * (120.0 / TAU) + Vec3::new(time, 0.0, 0.0),
+        ) * 0.3;
+
+        // Enhanced ring color with more contrast and variation
+        let ring_color = Color::from_srgb(
+            1.0 * (0.8 + 0.2 * ring_pattern + ring_noise),  // Brighter base
+            0.95 * (0.7 + 0.3 * ring_pattern + ring_noise), // Slight golden tint
+            0.9 * (0.6 + 0.4 * ring_pattern + ring_noise)   // Warmer tone
+        );
+
+        // Mix planet and ring colors with enhanced contrast
+        let final_color = if is_ring > 0.0 {
+            let ring_intensity = is_ring * (0.8 + 0.2 * ring_pattern);  // Vary ring intensity
+            // Denser bands let less through when the ring is blended
+            (ring_color * ring_intensity).with_alpha(0.45 + 0.5 * ring_pattern)
+        } else {
+            surface_color
+        };
+
+        if is_ring > 0.0 {
+            // The ring is a thin sheet: light its sunward face from whichever
+            // side it is seen
+            let light_dir = sun_position - fragment.world_position;
+            if fragment.normal.dot(&light_dir) < 0.0 {
+                let lit_side = Fragment { normal: -fragment.normal, ..*fragment };
+                return apply_enhanced_lighting(
+                    &lit_side,
+                    uniforms,
+                    sun_position,
+                    final_color,
+                    self.intensity,
+                );
+            }
+        }
+        apply_enhanced_lighting(fragment, uniforms, sun_position, final_color, self.intensity)
+    }
+}
+
+/// Cratered grey surface with dark maria.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MoonShader {
+    pub zoom: f32,
+    pub light_color: [f32; 3],
+    pub mare_color: [f32; 3],
+    pub intensity: f32,
+    /// Depth of the deepest craters as a fraction of the radius; 0 keeps the
+    /// sphere smooth.
+    pub relief: f32,
+}
+
+impl Default for MoonShader {
+    fn default() -> Self {
+        MoonShader {
+            zoom: 40.0,
+            light_color: [0.8, 0.8, 0.85],
+            mare_color: [0.2, 0.2, 0.25],
+            intensity: 1.2,
+            relief: 0.03,
+        }
+    }
+}
+
+impl MoonShader {
+    /// How deep the craters are at `position`, in `[0, 1]`.
+    fn crater_depth(&self, position: Vec3, noise: &FastNoiseLite) -> f32 {
+        let p = sphere_point(position, self.zoom);
+
+        // Large craters, plus smaller, more numerous ones
+        let large_craters = sample(noise, p).abs();
+        let small_craters = sample(noise, p * 4.0).abs();
+        (large_craters * 0.7 + small_craters * 0.3).clamp(0.0, 1.0)
+    }
+}
+
+impl FragmentShader for MoonShader {
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        let raised = displace(vertex, self.relief, |d| -self.crater_depth(d, &uniforms.noise));
+        vertex_shader(&raised, uniforms)
+    }
+
+    fn displacement_bound(&self) -> f32 {
+        self.relief.abs()
+    }
 
-    let mut r = base_color.r as f32 * (diffuse_intensity * attenuation + specular_intensity);
-    let mut g = base_color.g as f32 * (diffuse_intensity * attenuation + specular_intensity);
-    let mut b = base_color.b as f32 * (diffuse_intensity * attenuation + specular_intensity);
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let p = surface_point(fragment, self.zoom);
 
-    r = r.clamp(0.0, 255.0);
-    g = g.clamp(0.0, 255.0);
-    b = b.clamp(0.0, 255.0);
+        // Create surface texture variations
+        let surface_texture = sample(&uniforms.noise, p * 2.0);
 
-    Color::new(r as u8, g as u8, b as u8)
+        let crater_depth = self.crater_depth(fragment.object_position, &uniforms.noise);
+
+        // Create mare (dark areas) effect
+        let mare_effect = surface_texture.abs() * 0.3;
+
+        // Base colors for light and dark areas
+        let light_color = color(self.light_color);
+        let mare_color = color(self.mare_color);
+
+        // Mix colors based on crater depth and mare
+        let mixed_color = if mare_effect > 0.2 {
+            mare_color
+        } else {
+            let crater_factor = 1.0 - crater_depth * 0.5;
+            light_color * crater_factor
+        };
+
+        // Apply enhanced lighting with reduced intensity for more contrast
+        apply_enhanced_lighting(fragment, uniforms, sun_position, mixed_color, self.intensity)
+    }
+}
+
+/// Plain diffuse surface with no ambient term.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RockyPlanetShader {
+    pub color: [f32; 3],
+}
+
+impl Default for RockyPlanetShader {
+    fn default() -> Self {
+        RockyPlanetShader {
+            color: [0.8, 0.4, 0.2],
+        }
+    }
+}
+
+impl FragmentShader for RockyPlanetShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, sun_position: Vec3) -> Color {
+        let diffuse = fragment
+            .normal
+            .dot(&(sun_position - fragment.world_position).normalize())
+            .max(0.0);
+        let sunlight = uniforms.shadows.sun_visibility(fragment.world_position, sun_position);
+        color(self.color) * (diffuse * sunlight)
+    }
+}
+
+/// Unlit, noisy bands.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GasGiantShader {
+    pub zoom: f32,
+    pub color: [f32; 3],
+}
+
+impl Default for GasGiantShader {
+    fn default() -> Self {
+        GasGiantShader {
+            zoom: 5.0,
+            color: [0.8, 0.5, 1.0],
+        }
+    }
+}
+
+impl FragmentShader for GasGiantShader {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms, _sun_position: Vec3) -> Color {
+        // Depends on longitude only, giving bands that run pole to pole.
+        let p = surface_point(fragment, self.zoom);
+        let band_noise = sample(&uniforms.noise, Vec3::new(p.x, uniforms.time * 6.0, p.z));
+        color(self.color) * (0.5 + 0.5 * band_noise)
+    }
 }
 
-fn apply_enhanced_lighting(
+fn color([r, g, b]: [f32; 3]) -> Color {
+    Color::from_srgb(r, g, b)
+}
+
+pub(crate) fn apply_enhanced_lighting(
     fragment: &Fragment,
     uniforms: &Uniforms,
     sun_position: Vec3,
@@ -429,7 +688,7 @@ fn apply_enhanced_lighting(
     let diffuse = fragment.normal.dot(&light_dir).max(0.0);
     let diffuse_intensity = 2.0 * diffuse * intensity_multiplier;
 
-    let view_dir = (-fragment.world_position).normalize();
+    let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
     let reflect_dir = (2.0 * fragment.normal.dot(&light_dir) * fragment.normal - light_dir).normalize();
     let specular = reflect_dir.dot(&view_dir).max(0.0).powi(8);  // Reduced power for broader highlights
     let specular_intensity = 0.5 * specular * intensity_multiplier;
@@ -437,57 +696,12 @@ fn apply_enhanced_lighting(
     let distance_to_sun = (sun_position - fragment.world_position).magnitude();
     let attenuation = 1.0 / (1.0 + 0.003 * distance_to_sun * distance_to_sun);  // Reduced attenuation
 
+    // Other bodies in front of the Sun dim the direct light, not the ambient
+    let sunlight = uniforms.shadows.sun_visibility(fragment.world_position, sun_position);
+
     // Add ambient light to prevent completely dark areas
     let ambient = 0.2;
 
-    let mut r = base_color.r as f32 * (ambient + diffuse_intensity * attenuation + specular_intensity);
-    let mut g = base_color.g as f32 * (ambient + diffuse_intensity * attenuation + specular_intensity);
-    let mut b = base_color.b as f32 * (ambient + diffuse_intensity * attenuation + specular_intensity);
-
-    r = r.clamp(0.0, 255.0);
-    g = g.clamp(0.0, 255.0);
-    b = b.clamp(0.0, 255.0);
-
-    Color::new(r as u8, g as u8, b as u8)
-}
-
-use std::f32::consts::PI;
-
-pub enum ShaderType {
-    Star,
-    Mercury,
-    Venus,
-    Earth,
-    Mars,
-    Jupiter,
-    Saturn,
-    Moon,
-    RockyPlanet,
-    GasGiant,
-    Custom(fn(&Fragment, &Uniforms) -> Color),
-}
-
-pub fn rocky_planet_fragment_shader(
-    fragment: &Fragment,
-    uniforms: &Uniforms,
-    sun_position: Vec3,
-) -> Color {
-    let diffuse = fragment
-        .normal
-        .dot(&(sun_position - fragment.world_position).normalize())
-        .max(0.0);
-    let base_color = Color::new(200, 100, 50);
-    base_color * diffuse
-}
-
-pub fn gas_giant_fragment_shader(
-    fragment: &Fragment,
-    uniforms: &Uniforms,
-    sun_position: Vec3,
-) -> Color {
-    let band_noise = uniforms
-        .noise
-        .get_noise_2d(fragment.tex_coords.x * 5.0, uniforms.time as f32 * 0.1);
-    let base_color = Color::from_float(0.8, 0.5, 1.0);
-    base_color * (0.5 + 0.5 * band_noise)
+    // Left unclamped: sunlit highlights go past 1 and are tone-mapped later.
+    base_color * (ambient + (diffuse_intensity * attenuation + specular_intensity) * sunlight)
 }
@@ -0,0 +1,94 @@
+//! Shadows cast by whole bodies, treated as spheres, on everything else.
+//!
+//! Seen from a point being lit, the Sun and each body in front of it are
+//! discs on the sky. The fraction of the Sun's disc left uncovered scales
+//! the direct light, so a body wholly inside another's shadow is in
+//! umbra, and one near its edge in a penumbra as wide as the Sun looks.
+
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+/// A sphere that can block sunlight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Occluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// What can shadow the fragments of one frame, set by
+/// [`Scene::render`](crate::scene::Scene::render).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shadows {
+    /// Radius of the Sun's disc; 0 treats it as a point and gives hard
+    /// shadow edges.
+    pub sun_radius: f32,
+    pub occluders: Vec<Occluder>,
+}
+
+impl Shadows {
+    /// Fraction of the Sun at `sun_position` visible from `point`, from 0
+    /// in full shadow to 1 in full sunlight.
+    ///
+    /// Occluders containing `point` are skipped, so a body never shadows
+    /// its own surface (its night side is already unlit), and so are any
+    /// containing the Sun, which is usually a body itself. Several
+    /// occluders in front of the Sun are assumed not to overlap.
+    pub fn sun_visibility(&self, point: Vec3, sun_position: Vec3) -> f32 {
+        let to_sun = sun_position - point;
+        let sun_distance = to_sun.magnitude();
+        if sun_distance <= self.sun_radius || sun_distance == 0.0 {
+            return 1.0;
+        }
+        let sun_direction = to_sun / sun_distance;
+        let sun_angle = (self.sun_radius / sun_distance).asin();
+
+        let mut visible = 1.0;
+        for occluder in &self.occluders {
+            let offset = occluder.center - point;
+            let distance = offset.magnitude();
+            if distance <= occluder.radius
+                || (occluder.center - sun_position).magnitude() <= occluder.radius
+                || distance - occluder.radius >= sun_distance
+            {
+                continue;
+            }
+            let angle = (occluder.radius / distance).asin();
+            // Accurate for the tiny angles of distant bodies, unlike `acos`.
+            let separation = offset
+                .cross(&sun_direction)
+                .magnitude()
+                .atan2(offset.dot(&sun_direction));
+            visible *= 1.0 - covered_fraction(sun_angle, angle, separation);
+        }
+        visible
+    }
+}
+
+/// Fraction of a disc of angular radius `sun` covered by a disc of radius
+/// `occluder` whose center is `separation` away, treating both as flat.
+fn covered_fraction(sun: f32, occluder: f32, separation: f32) -> f32 {
+    if separation >= sun + occluder {
+        return 0.0;
+    }
+    if sun <= 0.0 {
+        // A point sun is either hidden or not.
+        return if separation < occluder { 1.0 } else { 0.0 };
+    }
+    if occluder >= sun && separation <= occluder - sun {
+        return 1.0;
+    }
+    if separation <= sun - occluder {
+        return (occluder / sun).powi(2);
+    }
+
+    // The lens where the two circles overlap.
+    let (r1, r2, d) = (sun, occluder, separation);
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let kite = 0.5
+        * ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2))
+            .max(0.0)
+            .sqrt();
+    let lens = r1 * r1 * a1 + r2 * r2 * a2 - kite;
+    (lens / (PI * r1 * r1)).clamp(0.0, 1.0)
+}
@@ -0,0 +1,55 @@
+//! Operators that compress the framebuffer's HDR colors into the displayable
+//! `[0, 1]` range.
+
+use crate::color::Color;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToneMapper {
+    /// Cuts off everything above 1, as an 8-bit pipeline would.
+    Clamp,
+    /// `x / (1 + x)`: keeps darks, rolls highlights off gently and never
+    /// reaches white.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve: a contrasty toe and a
+    /// shoulder that reaches white.
+    #[default]
+    Aces,
+    /// `1 - e^-x`, like film exposed for the framebuffer's `exposure`.
+    Exposure,
+}
+
+impl ToneMapper {
+    pub const ALL: [ToneMapper; 4] = [
+        ToneMapper::Clamp,
+        ToneMapper::Reinhard,
+        ToneMapper::Aces,
+        ToneMapper::Exposure,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMapper::Clamp => "clamp",
+            ToneMapper::Reinhard => "reinhard",
+            ToneMapper::Aces => "aces",
+            ToneMapper::Exposure => "exposure",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ToneMapper::ALL.into_iter().find(|mapper| mapper.name() == name)
+    }
+
+    /// Maps `color`, scaled by `exposure`, into `[0, 1]`.
+    pub fn apply(&self, color: Color, exposure: f32) -> Color {
+        let map = |x: f32| {
+            let x = (x * exposure).max(0.0);
+            match self {
+                ToneMapper::Clamp => x,
+                ToneMapper::Reinhard => x / (1.0 + x),
+                ToneMapper::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+                ToneMapper::Exposure => 1.0 - (-x).exp(),
+            }
+        };
+        Color::from_float(map(color.r), map(color.g), map(color.b)).clamp()
+    }
+}
@@ -3,28 +3,206 @@ use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use nalgebra_glm::{dot, Vec2, Vec3};
 
+/// Which faces the rasterizer discards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CullMode {
+    #[default]
+    Back,
+    Front,
+    /// Double-sided; nothing is culled.
+    None,
+}
+
+impl CullMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "back" => Some(CullMode::Back),
+            "front" => Some(CullMode::Front),
+            "none" => Some(CullMode::None),
+            _ => None,
+        }
+    }
+}
+
+/// Winding order, as seen by the camera, of a mesh's front faces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrontFace {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+impl FrontFace {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ccw" => Some(FrontFace::CounterClockwise),
+            "cw" => Some(FrontFace::Clockwise),
+            _ => None,
+        }
+    }
+}
+
+/// Per-mesh rasterizer settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RasterState {
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+}
+
+impl RasterState {
+    pub fn double_sided() -> Self {
+        RasterState {
+            cull_mode: CullMode::None,
+            front_face: FrontFace::CounterClockwise,
+        }
+    }
+
+    /// Whether a triangle with these screen-space corners should be skipped.
+    /// Degenerate triangles are always skipped.
+    pub fn culls(&self, a: &Vec3, b: &Vec3, c: &Vec3) -> bool {
+        let area = edge_function(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return true;
+        }
+
+        // The viewport flips y, so a counter-clockwise triangle in NDC has
+        // a positive signed area in screen space.
+        let counter_clockwise = area > 0.0;
+        let front_facing = match self.front_face {
+            FrontFace::CounterClockwise => counter_clockwise,
+            FrontFace::Clockwise => !counter_clockwise,
+        };
+
+        match self.cull_mode {
+            CullMode::Back => !front_facing,
+            CullMode::Front => front_facing,
+            CullMode::None => false,
+        }
+    }
+}
+
+/// Inclusive range of pixels, used to restrict rasterization to one tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelRect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl PixelRect {
+    pub const UNBOUNDED: PixelRect = PixelRect {
+        min_x: i32::MIN,
+        min_y: i32::MIN,
+        max_x: i32::MAX,
+        max_y: i32::MAX,
+    };
+
+    pub fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
+    }
+}
+
+/// Receives the fragments of a triangle as it is rasterized.
+pub trait FragmentSink {
+    /// Early depth test, run before any attribute besides depth is
+    /// interpolated. Returning `false` discards the fragment unshaded.
+    fn test(&mut self, x: usize, y: usize, depth: f32) -> bool {
+        let _ = (x, y, depth);
+        true
+    }
+
+    /// Called for every fragment that passed [`test`](Self::test).
+    fn fragment(&mut self, fragment: Fragment);
+}
+
+impl FragmentSink for Vec<Fragment> {
+    fn fragment(&mut self, fragment: Fragment) {
+        self.push(fragment);
+    }
+}
+
+/// Rasterizes a screen-space triangle into a list of fragments. Mostly
+/// useful for tests; the renderer streams fragments with [`rasterize`].
 pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
     let mut fragments = Vec::new();
+    rasterize(v1, v2, v3, &PixelRect::UNBOUNDED, &mut fragments);
+    fragments
+}
+
+/// Rasterizes a screen-space triangle, handing each covered pixel inside
+/// `bounds` to `sink`.
+///
+/// A pixel center lying exactly on an edge shared by two triangles belongs
+/// to only one of them (a top-left fill rule), so meshes drawn with
+/// blending and no depth writes cover each pixel once.
+///
+/// Depth is interpolated linearly in screen space; every other attribute is
+/// interpolated perspective-correctly by weighting each vertex with the
+/// `1/w` of its clip-space position.
+pub fn rasterize(
+    v1: &Vertex,
+    v2: &Vertex,
+    v3: &Vertex,
+    bounds: &PixelRect,
+    sink: &mut impl FragmentSink,
+) {
+    // The fill rule needs one winding; swapping two corners flips it.
+    let (v2, v3) = if edge_function(
+        &v1.transformed_position,
+        &v2.transformed_position,
+        &v3.transformed_position,
+    ) < 0.0
+    {
+        (v3, v2)
+    } else {
+        (v2, v3)
+    };
     let (a, b, c) = (
         v1.transformed_position,
         v2.transformed_position,
         v3.transformed_position,
     );
     let (t1, t2, t3) = (v1.tex_coords, v2.tex_coords, v3.tex_coords);
+    let (inv_w1, inv_w2, inv_w3) = (
+        1.0 / v1.clip_position.w,
+        1.0 / v2.clip_position.w,
+        1.0 / v3.clip_position.w,
+    );
 
     let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+    let (min_x, min_y) = (min_x.max(bounds.min_x), min_y.max(bounds.min_y));
+    let (max_x, max_y) = (max_x.min(bounds.max_x), max_y.min(bounds.max_y));
 
     let light_dir = Vec3::new(0.0, 0.0, 1.0);
 
     let triangle_area = edge_function(&a, &b, &c);
+    if triangle_area <= 0.0 {
+        // Degenerate: no pixel centers inside.
+        return;
+    }
 
     for y in min_y..=max_y {
         for x in min_x..=max_x {
             let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
 
-            let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+            let (e1, e2, e3) = (edge(&b, &c, &point), edge(&c, &a, &point), edge(&a, &b, &point));
+            if covers(e1, &b, &c) && covers(e2, &c, &a) && covers(e3, &a, &b) {
+                let (w1, w2, w3) = (e1 / triangle_area, e2 / triangle_area, e3 / triangle_area);
+                let depth = a.z * w1 + b.z * w2 + c.z * w3;
+                if !sink.test(x as usize, y as usize, depth) {
+                    continue;
+                }
+
+                // Screen-space weights to perspective-correct ones.
+                let (p1, p2, p3) = (w1 * inv_w1, w2 * inv_w2, w3 * inv_w3);
+                let sum = p1 + p2 + p3;
+                let (w1, w2, w3) = (p1 / sum, p2 / sum, p3 / sum);
 
-            if w1 >= 0.0 && w1 <= 1.0 && w2 >= 0.0 && w2 <= 1.0 && w3 >= 0.0 && w3 <= 1.0 {
                 let normal = v1.transformed_normal * w1
                     + v2.transformed_normal * w2
                     + v3.transformed_normal * w3;
@@ -34,30 +212,30 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
 
                 let color = Color::new(100, 100, 100);
 
-                let depth = a.z * w1 + b.z * w2 + c.z * w3;
-
-                let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+                let world_position =
+                    v1.world_position * w1 + v2.world_position * w2 + v3.world_position * w3;
+                let object_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
                 let tex_u = t1.x * w1 + t2.x * w2 + t3.x * w3;
                 let tex_v = t1.y * w1 + t2.y * w2 + t3.y * w3;
 
-                fragments.push(Fragment::new(
-                    Vec2::new(x as f32, y as f32),
+                sink.fragment(Fragment {
+                    position: Vec2::new(x as f32, y as f32),
                     color,
                     depth,
                     normal,
                     intensity,
-                    vertex_position,
-                    Vec2::new(tex_u, tex_v),
-                ));
+                    world_position,
+                    object_position,
+                    tex_coords: Vec2::new(tex_u, tex_v),
+                });
             }
         }
     }
-
-    fragments
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
+/// Pixel bounding box of a screen-space triangle, as `(min_x, min_y, max_x, max_y)`.
+pub fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
     let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
@@ -66,12 +244,25 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
+/// `edge_function(from, to, p)`, always evaluated from the same end of the
+/// edge so the two triangles sharing it get exactly opposite values.
+fn edge(from: &Vec3, to: &Vec3, p: &Vec3) -> f32 {
+    if (from.x, from.y) <= (to.x, to.y) {
+        edge_function(from, to, p)
+    } else {
+        -edge_function(to, from, p)
+    }
+}
 
-    (w1, w2, w3)
+/// Whether a point at `value` from the edge `from -> to` of a positively
+/// wound triangle is inside it. Points on the edge go to whichever of the
+/// two triangles sharing it sees it as a top or left edge.
+fn covers(value: f32, from: &Vec3, to: &Vec3) -> bool {
+    if value != 0.0 {
+        return value > 0.0;
+    }
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
 }
 
 fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
@@ -1,5 +1,5 @@
 use crate::color::Color;
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 
 #[derive(Clone, Debug)]
 pub struct Vertex {
@@ -10,6 +10,7 @@ pub struct Vertex {
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
     pub world_position: Vec3,
+    pub clip_position: Vec4,
 }
 
 impl Vertex {
@@ -22,6 +23,7 @@ impl Vertex {
             transformed_position: position,
             transformed_normal: normal,
             world_position: position,
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
         }
     }
 
@@ -34,6 +36,22 @@ impl Vertex {
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 0.0, 0.0),
             world_position: position,
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
+        }
+    }
+
+    /// Linear interpolation of every attribute, used when clipping splits an
+    /// edge.
+    pub fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+        Vertex {
+            position: self.position.lerp(&other.position, t),
+            normal: self.normal.lerp(&other.normal, t),
+            tex_coords: self.tex_coords.lerp(&other.tex_coords, t),
+            color: self.color.lerp(&other.color, t),
+            transformed_position: self.transformed_position.lerp(&other.transformed_position, t),
+            transformed_normal: self.transformed_normal.lerp(&other.transformed_normal, t),
+            world_position: self.world_position.lerp(&other.world_position, t),
+            clip_position: self.clip_position.lerp(&other.clip_position, t),
         }
     }
 
@@ -54,6 +72,7 @@ impl Default for Vertex {
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 1.0, 0.0),
             world_position: Vec3::new(0.0, 0.0, 0.0),
+            clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
         }
     }
 }
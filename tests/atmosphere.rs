@@ -0,0 +1,96 @@
+use nalgebra_glm::Vec3;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{Atmosphere, Camera, Framebuffer, LodMeshes, ShaderRegistry, Uniforms};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+
+#[test]
+fn skies_are_blue_overhead_and_red_at_sunset() {
+    let air = Atmosphere::default();
+    let ground = Vec3::new(0.0, 1.001, 0.0);
+
+    // Midday, looking up away from the sun.
+    let sun = Vec3::new(1.0, 1.0, 0.0).normalize();
+    let (noon, _) = air.scatter(ground, Vec3::y(), sun);
+    assert!(noon.z > 2.0 * noon.x, "{:?}", noon);
+
+    // Sunset, looking towards the sun on the horizon.
+    let horizon = Vec3::new(1.0, 0.02, 0.0).normalize();
+    let (sunset, _) = air.scatter(ground, horizon, horizon);
+    assert!(sunset.x > sunset.z, "{:?}", sunset);
+}
+
+#[test]
+fn limbs_glow_on_the_day_side_only() {
+    let air = Atmosphere::default();
+    let sun = Vec3::new(-1.0, 0.0, 0.0);
+    let view = Vec3::new(0.0, 0.0, -1.0);
+    let grazing = |x: f32| air.scatter(Vec3::new(x, 0.0, 10.0), view, sun);
+
+    let (day, day_transmittance) = grazing(-1.03);
+    let (night, night_transmittance) = grazing(1.03);
+    assert!(day.z > day.x && day.z > 0.05, "{:?}", day);
+    assert_eq!(night, Vec3::zeros());
+    // Air still dims what is behind it at night.
+    assert_eq!(day_transmittance, night_transmittance);
+    assert!(night_transmittance.z < 1.0);
+
+    // Rays that miss the shell do nothing.
+    let (outside, transmittance) = grazing(1.2);
+    assert_eq!(outside, Vec3::zeros());
+    assert_eq!(transmittance, Vec3::repeat(1.0));
+}
+
+#[test]
+fn blue_absorbing_haze_looks_yellow() {
+    let haze = Atmosphere {
+        rayleigh: [0.2, 0.3, 0.5],
+        mie: [30.0, 30.0, 30.0],
+        mie_absorption: [1.0, 4.0, 15.0],
+        mie_scale_height: 0.04,
+        ..Atmosphere::default()
+    };
+    let sun = Vec3::new(0.0, 0.0, 1.0);
+    let (scattered, _) = haze.scatter(Vec3::new(0.0, 0.0, 10.0), -sun, sun);
+    assert!(scattered.x > scattered.y && scattered.y > scattered.z, "{:?}", scattered);
+}
+
+const SCENE: &str = r#"
+[[body]]
+name = "Earth"
+shader = "rocky_planet"
+scale = 1.0
+
+[body.atmosphere]
+height = 0.2
+"#;
+
+#[test]
+fn shells_draw_around_their_body() {
+    let shaders = ShaderRegistry::default();
+    let mut scene = parse_scene(SCENE, LodMeshes::uv_sphere(), &shaders).expect("scene should load");
+    assert!((scene.bounding_sphere(0).1 - 1.2).abs() < 1e-3);
+
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    let mut uniforms = Uniforms::new(WIDTH, HEIGHT);
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 6.0), Vec3::zeros(), Vec3::y());
+    scene.sun_position = Vec3::new(0.0, 0.0, 50.0);
+    scene.render(&mut framebuffer, &mut uniforms, &camera);
+
+    // Sky beyond the surface: lit, but not in the depth buffer.
+    let sky = framebuffer
+        .color
+        .iter()
+        .zip(&framebuffer.zbuffer)
+        .filter(|(color, depth)| depth.is_infinite() && color.b > 0.01)
+        .count();
+    assert!(sky > 0);
+
+    let invalid = SCENE.replace("height = 0.2", "height = -0.2");
+    let message = parse_scene(&invalid, LodMeshes::uv_sphere(), &shaders)
+        .err()
+        .expect("negative height should fail")
+        .to_string();
+    assert!(message.contains("Earth") && message.contains("height"), "{}", message);
+}
@@ -0,0 +1,187 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3};
+use rust_planets::color::Color;
+use rust_planets::fragment::Fragment;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{
+    annulus, create_model_matrix, render as draw, BlendMode, Camera, FragmentShader, Framebuffer,
+    LodMeshes, RasterState, ShaderRegistry, Uniforms, Vertex,
+};
+use std::f32::consts::FRAC_PI_2;
+use serde::Deserialize;
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+
+/// Unlit color with an opacity.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Tinted {
+    color: [f32; 4],
+}
+
+impl FragmentShader for Tinted {
+    fn fragment(&self, _fragment: &Fragment, _uniforms: &Uniforms, _sun: Vec3) -> Color {
+        let [r, g, b, a] = self.color;
+        Color::from_rgba(r, g, b, a)
+    }
+}
+
+fn uniforms() -> Uniforms {
+    Uniforms::new(WIDTH, HEIGHT)
+}
+
+/// Renders `bodies` (TOML `[[body]]` tables) seen from z = 10 and returns
+/// the framebuffer.
+fn render(bodies: &str) -> Framebuffer {
+    let mut shaders = ShaderRegistry::default();
+    shaders.register_params::<Tinted>("tinted");
+    let mut scene = parse_scene(bodies, LodMeshes::uv_sphere(), &shaders).expect("scene should load");
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::y());
+    scene.render(&mut framebuffer, &mut uniforms(), &camera);
+    framebuffer
+}
+
+fn center(framebuffer: &Framebuffer) -> usize {
+    framebuffer.height / 2 * framebuffer.width + framebuffer.width / 2
+}
+
+fn close(a: Color, b: Color) -> bool {
+    (a.r - b.r).abs() < 1e-4 && (a.g - b.g).abs() < 1e-4 && (a.b - b.b).abs() < 1e-4
+}
+
+#[test]
+fn blend_modes_combine_colors() {
+    let destination = Color::from_float(0.2, 0.4, 0.6);
+    let source = Color::from_rgba(1.0, 0.0, 0.0, 0.25);
+
+    assert_eq!(BlendMode::Replace.blend(source, destination), source);
+    assert!(close(
+        BlendMode::Alpha.blend(source, destination),
+        Color::from_float(0.4, 0.3, 0.45)
+    ));
+    assert!(close(
+        BlendMode::Additive.blend(source, destination),
+        Color::from_float(0.45, 0.4, 0.6)
+    ));
+    assert!(close(
+        BlendMode::Premultiplied.blend(source.premultiplied(), destination),
+        BlendMode::Alpha.blend(source, destination)
+    ));
+    assert_eq!(BlendMode::Alpha.blend(source.with_alpha(1.0), destination), source.with_alpha(1.0));
+    assert_eq!(BlendMode::Alpha.blend(source.with_alpha(0.0), destination), destination);
+    assert_eq!(BlendMode::from_name("premultiplied"), Some(BlendMode::Premultiplied));
+}
+
+#[test]
+fn points_honor_blending_and_depth_writes() {
+    let mut framebuffer = Framebuffer::new(2, 1);
+    framebuffer.clear();
+    framebuffer.set_current_color(0x808080);
+    framebuffer.set_blend_mode(BlendMode::Additive);
+    framebuffer.set_depth_write(false);
+    framebuffer.point(0, 0, 1.0);
+    framebuffer.point(0, 0, 1.0);
+    assert!(close(framebuffer.color[0], Color::from_hex(0x808080) * 2.0));
+    assert_eq!(framebuffer.zbuffer[0], f32::INFINITY);
+
+    framebuffer.set_blend_mode(BlendMode::Replace);
+    framebuffer.set_depth_write(true);
+    framebuffer.point(1, 0, 1.0);
+    assert_eq!(framebuffer.zbuffer[1], 1.0);
+    framebuffer.point(1, 0, 2.0);
+    assert_eq!(framebuffer.color[1], Color::from_hex(0x808080));
+}
+
+#[test]
+fn translucent_bodies_draw_back_to_front() {
+    let near = r#"
+        [[body]]
+        name = "Near"
+        shader = "tinted"
+        shader_params = { color = [1.0, 0.0, 0.0, 0.5] }
+        position = [0.0, 0.0, 2.0]
+        scale = 1.0
+        blend = "alpha"
+    "#;
+    let far = r#"
+        [[body]]
+        name = "Far"
+        shader = "tinted"
+        shader_params = { color = [0.0, 0.0, 1.0, 0.5] }
+        position = [0.0, 0.0, -2.0]
+        scale = 1.0
+        blend = "alpha"
+    "#;
+    let opaque = r#"
+        [[body]]
+        name = "Behind"
+        shader = "tinted"
+        shader_params = { color = [0.0, 1.0, 0.0, 1.0] }
+        position = [0.0, 0.0, -6.0]
+        scale = 3.0
+    "#;
+
+    // Declaration order doesn't matter: the far layer is always blended
+    // first, and the opaque body behind both shows through.
+    let forwards = render(&format!("{}{}{}", near, far, opaque));
+    let backwards = render(&format!("{}{}{}", opaque, far, near));
+    let index = center(&forwards);
+    assert_eq!(forwards.color[index], backwards.color[index]);
+    assert!(close(forwards.color[index], Color::from_float(0.5, 0.25, 0.25)), "{}", forwards.color[index]);
+
+    // Only the opaque body wrote depth.
+    assert_eq!(forwards.zbuffer, backwards.zbuffer);
+    let translucent_only = render(&format!("{}{}", near, far));
+    assert!(translucent_only.zbuffer.iter().all(|depth| *depth == f32::INFINITY));
+    assert!(close(translucent_only.color[index], Color::from_float(0.5, 0.0, 0.25)));
+}
+
+/// Draws `vertices` at half opacity over black without depth writes and
+/// checks that every covered pixel got exactly one layer.
+fn check_single_coverage(vertices: &[Vertex], uniforms: &Uniforms) {
+    let shader = Tinted {
+        color: [1.0, 1.0, 1.0, 0.5],
+    };
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    framebuffer.set_background_color(0x000000);
+    framebuffer.clear();
+    framebuffer.set_blend_mode(BlendMode::Alpha);
+    framebuffer.set_depth_write(false);
+    draw(&mut framebuffer, uniforms, vertices, &shader, Vec3::zeros(), RasterState::double_sided());
+
+    let covered: Vec<&Color> = framebuffer.color.iter().filter(|c| !c.is_black()).collect();
+    assert!(covered.len() > 1000, "{}", covered.len());
+    for color in covered {
+        assert!(close(*color, Color::from_float(0.5, 0.5, 0.5)), "{}", color);
+    }
+}
+
+#[test]
+fn shared_edges_are_blended_once() {
+    // A 64-pixel square whose diagonal runs exactly through pixel centers.
+    let corner = |x: f32, y: f32| Vertex::new(Vec3::new(x, y, 0.0), Vec3::z(), Vec2::zeros());
+    let quad = [
+        corner(-1.0, -1.0),
+        corner(1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, 1.0),
+    ];
+    let mut uniforms = uniforms();
+    uniforms.projection_matrix = Mat4::identity();
+    uniforms.viewport_matrix = Mat4::new(
+        32.0, 0.0, 0.0, 100.0,
+        0.0, 32.0, 0.0, 75.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    check_single_coverage(&quad, &uniforms);
+
+    // Spokes and diagonals of a ring turned to face the camera.
+    let mut uniforms = self::uniforms();
+    let facing = Vec3::new(FRAC_PI_2, 0.0, 0.0);
+    uniforms.model_matrix = create_model_matrix(Vec3::new(0.1, 0.05, -4.0), 1.0, facing);
+    check_single_coverage(&annulus(0.4, 1.0, 48), &uniforms);
+}
@@ -0,0 +1,87 @@
+use rust_planets::color::Color;
+use rust_planets::{Bloom, Framebuffer};
+
+const SIZE: usize = 128;
+
+/// A dark frame with one square of `brightness` in the middle.
+fn frame(brightness: f32) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.set_background_color(0x000000);
+    framebuffer.clear();
+    for y in 62..66 {
+        for x in 62..66 {
+            framebuffer.color[y * SIZE + x] = Color::from_float(brightness, brightness, brightness);
+        }
+    }
+    framebuffer
+}
+
+fn at(framebuffer: &Framebuffer, x: usize, y: usize) -> f32 {
+    framebuffer.color[y * SIZE + x].luminance()
+}
+
+#[test]
+fn only_pixels_over_the_threshold_glow() {
+    let bloom = Bloom::default();
+
+    let mut dim = frame(bloom.threshold * 0.9);
+    let before = dim.color.clone();
+    bloom.apply(&mut dim);
+    assert_eq!(dim.color, before);
+
+    let mut bright = frame(bloom.threshold * 4.0);
+    Bloom { intensity: 0.0, ..bloom.clone() }.apply(&mut bright);
+    assert_eq!(at(&bright, 40, 64), 0.0);
+    bloom.apply(&mut bright);
+    assert!(at(&bright, 40, 64) > 0.0);
+}
+
+#[test]
+fn glow_falls_off_evenly_and_far() {
+    let bloom = Bloom::default();
+    let mut framebuffer = frame(50.0);
+    bloom.apply(&mut framebuffer);
+
+    // Symmetric about the square, horizontally and vertically.
+    for distance in [4, 10, 30] {
+        let left = at(&framebuffer, 62 - distance, 64);
+        let right = at(&framebuffer, 65 + distance, 64);
+        let up = at(&framebuffer, 64, 62 - distance);
+        assert!((left - right).abs() < 1e-3 * left.max(1e-6), "{} vs {}", left, right);
+        assert!((left - up).abs() < 0.05 * left.max(1e-6), "{} vs {}", left, up);
+    }
+
+    // Fading with distance, but the coarse scales still reach a long way.
+    let near = at(&framebuffer, 58, 64);
+    let middle = at(&framebuffer, 50, 64);
+    let far = at(&framebuffer, 30, 64);
+    assert!(near > middle && middle > far && far > 0.0, "{} {} {}", near, middle, far);
+
+    // Stronger bloom, brighter halo.
+    let mut strong = frame(50.0);
+    Bloom { intensity: bloom.intensity * 2.0, ..bloom }.apply(&mut strong);
+    assert!(at(&strong, 50, 64) > middle * 1.9);
+}
+
+#[test]
+fn odd_edges_are_not_dropped() {
+    // Odd at every scale, so each halving has a trailing row and column.
+    const ODD: usize = 63;
+    let bloom = Bloom {
+        levels: 3,
+        ..Bloom::default()
+    };
+    let glow_from = |x: usize, y: usize| {
+        let mut framebuffer = Framebuffer::new(ODD, ODD);
+        framebuffer.set_background_color(0x000000);
+        framebuffer.clear();
+        framebuffer.color[y * ODD + x] = Color::from_float(100.0, 100.0, 100.0);
+        bloom.apply(&mut framebuffer);
+        let total: f32 = framebuffer.color.iter().map(|color| color.luminance()).sum();
+        total - 100.0 * Color::from_float(1.0, 1.0, 1.0).luminance()
+    };
+
+    let corner = glow_from(ODD - 1, ODD - 1);
+    let inside = glow_from(ODD - 3, ODD - 3);
+    assert!(corner > 0.5 * inside, "corner {} vs inside {}", corner, inside);
+}
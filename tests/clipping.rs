@@ -0,0 +1,80 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use rust_planets::clipping::clip_triangle;
+use rust_planets::Vertex;
+
+/// A vertex at `clip` whose texture coordinates are its clip-space X and Z,
+/// so any correctly interpolated vertex keeps them in step with its
+/// position.
+fn vertex(clip: Vec4) -> Vertex {
+    let mut vertex = Vertex::new(Vec3::zeros(), Vec3::z(), Vec2::new(clip.x, clip.z));
+    vertex.clip_position = clip;
+    vertex
+}
+
+#[test]
+fn triangles_crossing_the_near_plane_become_quads() {
+    let behind = vertex(Vec4::new(0.0, 0.0, -2.0, 1.0));
+    let left = vertex(Vec4::new(-0.5, 0.0, 0.5, 1.0));
+    let right = vertex(Vec4::new(0.5, 0.0, 0.5, 1.0));
+
+    let polygon = clip_triangle(&behind, &left, &right);
+    assert_eq!(polygon.len(), 4);
+    for vertex in &polygon {
+        let clip = vertex.clip_position;
+        assert!(clip.w + clip.z >= -1e-6, "{:?}", clip);
+    }
+    // Two of them are new, on the near plane.
+    let on_plane = polygon
+        .iter()
+        .filter(|v| (v.clip_position.w + v.clip_position.z).abs() < 1e-6)
+        .count();
+    assert_eq!(on_plane, 2);
+}
+
+#[test]
+fn attributes_are_interpolated_at_the_split() {
+    let behind = vertex(Vec4::new(0.0, 0.0, -2.0, 1.0));
+    let left = vertex(Vec4::new(-0.5, 0.0, 0.5, 1.0));
+    let right = vertex(Vec4::new(0.5, 0.0, 0.5, 1.0));
+
+    let polygon = clip_triangle(&behind, &left, &right);
+    for vertex in &polygon {
+        let clip = vertex.clip_position;
+        assert!((vertex.tex_coords - Vec2::new(clip.x, clip.z)).magnitude() < 1e-6);
+    }
+
+    // From `behind` to `right` the near-plane distance goes from -1 to 1.5,
+    // so the split is 40% of the way along.
+    let split = polygon
+        .iter()
+        .find(|v| v.clip_position.x > 0.0 && v.clip_position.z < 0.0)
+        .expect("a vertex on the right edge");
+    assert!((split.clip_position - Vec4::new(0.2, 0.0, -1.0, 1.0)).magnitude() < 1e-6);
+    assert!((split.tex_coords - Vec2::new(0.2, -1.0)).magnitude() < 1e-6);
+}
+
+#[test]
+fn triangles_fully_outside_are_dropped() {
+    // All beyond the right plane, though each corner is inside the others.
+    let polygon = clip_triangle(
+        &vertex(Vec4::new(1.5, 0.0, 0.0, 1.0)),
+        &vertex(Vec4::new(2.0, 0.5, 0.0, 1.0)),
+        &vertex(Vec4::new(3.0, -0.5, 0.5, 1.0)),
+    );
+    assert!(polygon.is_empty());
+}
+
+#[test]
+fn triangles_fully_inside_are_unchanged() {
+    let corners = [
+        vertex(Vec4::new(-0.5, -0.5, 0.2, 1.0)),
+        vertex(Vec4::new(0.5, -0.5, 0.3, 1.0)),
+        vertex(Vec4::new(0.0, 0.5, 0.4, 1.0)),
+    ];
+    let polygon = clip_triangle(&corners[0], &corners[1], &corners[2]);
+    assert_eq!(polygon.len(), 3);
+    for (clipped, original) in polygon.iter().zip(&corners) {
+        assert_eq!(clipped.clip_position, original.clip_position);
+        assert_eq!(clipped.tex_coords, original.tex_coords);
+    }
+}
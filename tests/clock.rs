@@ -0,0 +1,62 @@
+use rust_planets::SimulationClock;
+
+#[test]
+fn partial_steps_accumulate_across_frames() {
+    let mut clock = SimulationClock::new(0.25);
+    assert_eq!(clock.advance(0.1), 0);
+    assert_eq!(clock.advance(0.1), 0);
+    // 0.3 accumulated: one step due, 0.05 left over.
+    assert_eq!(clock.advance(0.1), 1);
+    assert_eq!(clock.time(), 0.25);
+    assert_eq!(clock.advance(0.5), 2);
+    assert_eq!(clock.time(), 0.75);
+
+    // Faster time gives proportionally more steps.
+    clock.set_time_scale(4.0);
+    assert_eq!(clock.advance(0.5), 8);
+    assert_eq!(clock.time(), 2.75);
+}
+
+#[test]
+fn reverse_runs_time_backwards() {
+    let mut clock = SimulationClock::new(0.25);
+    assert_eq!(clock.advance(1.0), 4);
+
+    clock.set_time_scale(-1.0);
+    assert_eq!(clock.step(), -0.25);
+    assert_eq!(clock.advance(0.5), 2);
+    assert_eq!(clock.time(), 0.5);
+
+    // Leftover time is dropped on a change of direction rather than replayed.
+    assert_eq!(clock.advance(0.2), 0);
+    clock.set_time_scale(1.0);
+    assert_eq!(clock.advance(0.2), 0);
+    assert_eq!(clock.time(), 0.5);
+}
+
+#[test]
+fn paused_clocks_do_not_move_or_accumulate() {
+    let mut clock = SimulationClock::new(0.25);
+    clock.toggle_pause();
+    assert!(clock.is_paused());
+    assert_eq!(clock.advance(10.0), 0);
+    assert_eq!(clock.time(), 0.0);
+
+    clock.set_paused(false);
+    assert_eq!(clock.advance(0.1), 0);
+    assert_eq!(clock.advance(0.2), 1);
+}
+
+#[test]
+fn time_beyond_the_step_cap_is_dropped() {
+    let mut clock = SimulationClock::new(0.25);
+    clock.set_max_steps(3);
+    assert_eq!(clock.advance(100.0), 3);
+    assert_eq!(clock.time(), 0.75);
+    // Nothing of the long frame is carried into the next one.
+    assert_eq!(clock.advance(0.1), 0);
+
+    // Exactly at the cap, the remainder is kept.
+    assert_eq!(clock.advance(0.7), 3);
+    assert_eq!(clock.advance(0.25), 1);
+}
@@ -0,0 +1,82 @@
+use nalgebra_glm::Vec3;
+use rust_planets::clipping::Frustum;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{
+    create_view_matrix, Camera, Framebuffer, LodMeshes, ShaderRegistry, Uniforms,
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+
+const SCENE: &str = r#"
+[[body]]
+name = "Ahead"
+shader = "rocky_planet"
+scale = 1.0
+
+[[body]]
+name = "Behind"
+shader = "rocky_planet"
+position = [0.0, 0.0, 20.0]
+scale = 1.0
+
+[[body]]
+name = "Aside"
+shader = "rocky_planet"
+position = [100.0, 0.0, 0.0]
+scale = 1.0
+
+# Only the ring reaches into view.
+[[body]]
+name = "Ringed"
+shader = "saturn"
+position = [7.0, 0.0, 0.0]
+scale = 0.5
+
+[body.ring]
+outer_radius = 2.5
+tilt = [34.4, 0.0, 0.0]
+"#;
+
+fn uniforms() -> Uniforms {
+    Uniforms::new(WIDTH, HEIGHT)
+}
+
+#[test]
+fn bodies_outside_the_frustum_are_culled() {
+    let mut scene = parse_scene(SCENE, LodMeshes::uv_sphere(), &ShaderRegistry::default())
+        .expect("scene should load");
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    let mut uniforms = uniforms();
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::y());
+
+    let stats = scene.render(&mut framebuffer, &mut uniforms, &camera);
+    assert_eq!(stats.culled, 2);
+    assert_eq!(stats.bodies, 2);
+
+    // The ring really is on screen, right of the planet at the origin.
+    let ring_pixels = (0..framebuffer.height)
+        .flat_map(|y| (framebuffer.width * 9 / 10..framebuffer.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| framebuffer.zbuffer[y * framebuffer.width + x].is_finite())
+        .count();
+    assert!(ring_pixels > 0);
+
+    // Hidden bodies don't count as culled.
+    scene.bodies[1].visible = false;
+    let stats = scene.render(&mut framebuffer, &mut uniforms, &camera);
+    assert_eq!(stats.culled, 1);
+}
+
+#[test]
+fn spheres_touching_the_frustum_are_kept() {
+    let uniforms = uniforms();
+    let view = create_view_matrix(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::y());
+    let frustum = Frustum::from_matrix(&(uniforms.projection_matrix * view));
+
+    assert!(frustum.intersects_sphere(&Vec3::zeros(), 1.0));
+    assert!(!frustum.intersects_sphere(&Vec3::new(0.0, 0.0, 20.0), 1.0));
+    // Straddling the near plane, centered just behind the camera.
+    assert!(frustum.intersects_sphere(&Vec3::new(0.0, 0.0, 10.5), 1.0));
+    // Beyond the far plane.
+    assert!(!frustum.intersects_sphere(&Vec3::new(0.0, 0.0, -2000.0), 1.0));
+}
@@ -0,0 +1,64 @@
+use rust_planets::color::Color;
+use rust_planets::headless::parse_frame_range;
+use rust_planets::{Framebuffer, ToneMapper};
+
+#[test]
+fn frame_ranges_are_half_open() {
+    assert_eq!(parse_frame_range("0..120"), Ok(0..120));
+    assert_eq!(parse_frame_range("10..11"), Ok(10..11));
+    assert_eq!(parse_frame_range("42"), Ok(42..43));
+    assert_eq!(parse_frame_range("0"), Ok(0..1));
+}
+
+#[test]
+fn the_last_frame_number_cannot_be_rendered_alone() {
+    let max = u32::MAX.to_string();
+    let err = parse_frame_range(&max).expect_err("no frame after u32::MAX");
+    assert!(err.contains("too large"), "{}", err);
+
+    // As the exclusive end of a range it is fine.
+    assert_eq!(parse_frame_range(&format!("0..{}", max)), Ok(0..u32::MAX));
+}
+
+#[test]
+fn empty_and_reversed_ranges_are_rejected() {
+    for value in ["5..5", "20..10"] {
+        let err = parse_frame_range(value).expect_err(value);
+        assert!(err.contains("empty frame range"), "{}", err);
+    }
+    for value in ["", "-1", "1..", "..5", "a..b", "1...3", "4294967296"] {
+        let err = parse_frame_range(value).expect_err(value);
+        assert!(err.contains("invalid frame number"), "{:?}: {}", value, err);
+    }
+}
+
+#[test]
+fn images_hold_the_resolved_srgb_pixels() {
+    let mut framebuffer = Framebuffer::new(3, 2);
+    framebuffer.set_tone_mapper(ToneMapper::Clamp);
+    framebuffer.color[0] = Color::from_float(1.0, 0.0, 0.0);
+    framebuffer.color[2] = Color::from_float(0.0, 0.5, 0.0);
+    framebuffer.color[4] = Color::from_float(0.18, 0.18, 0.18);
+    framebuffer.resolve();
+
+    let image = framebuffer.to_image();
+    assert_eq!(image.dimensions(), (3, 2));
+    assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0]);
+    assert_eq!(image.get_pixel(2, 0).0, [0, 0xBC, 0]);
+    assert_eq!(image.get_pixel(1, 1).0, [0x76, 0x76, 0x76]);
+    assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0]);
+}
+
+#[test]
+fn saved_pngs_match_the_image() {
+    let mut framebuffer = Framebuffer::new(4, 3);
+    framebuffer.color[5] = Color::from_float(0.3, 0.6, 0.9);
+    framebuffer.resolve();
+
+    let path = std::env::temp_dir().join(format!("rust_planets_{}.png", std::process::id()));
+    framebuffer.save_png(&path).expect("png should save");
+    let saved = image::open(&path).expect("png should load").to_rgb8();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(saved, framebuffer.to_image());
+}
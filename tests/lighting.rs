@@ -0,0 +1,67 @@
+use nalgebra_glm::Vec3;
+use rust_planets::shaders::RockyPlanetShader;
+use rust_planets::{
+    create_model_matrix, create_view_matrix, render, Framebuffer, Obj, RasterState, Uniforms,
+};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+
+/// Renders a diffuse planet at `planet` seen head-on along -Z, lit by a sun
+/// at `sun`, and returns the mean brightness of its left and right halves.
+fn lit_halves(planet: Vec3, sun: Vec3) -> (f32, f32) {
+    let sphere = Obj::load("tests/models/sphere.obj").expect("sphere.obj");
+    let eye = planet + Vec3::new(0.0, 0.0, 4.0);
+    let uniforms = Uniforms {
+        model_matrix: create_model_matrix(planet, 1.0, Vec3::zeros()),
+        view_matrix: create_view_matrix(eye, planet, Vec3::new(0.0, 1.0, 0.0)),
+        camera_position: eye,
+        ..Uniforms::new(WIDTH as f32, HEIGHT as f32)
+    };
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    render(
+        &mut framebuffer,
+        &uniforms,
+        &sphere.get_vertex_array(),
+        &RockyPlanetShader::default(),
+        sun,
+        RasterState::default(),
+    );
+    framebuffer.resolve();
+
+    let mut sums = [0.0; 2];
+    let mut counts = [0; 2];
+    for (index, &pixel) in framebuffer.buffer.iter().enumerate() {
+        if !framebuffer.zbuffer[index].is_finite() {
+            continue;
+        }
+        let half = usize::from(index % WIDTH >= WIDTH / 2);
+        let brightness = ((pixel >> 16) & 0xFF) + ((pixel >> 8) & 0xFF) + (pixel & 0xFF);
+        sums[half] += brightness as f32;
+        counts[half] += 1;
+    }
+    assert!(counts[0] > 0 && counts[1] > 0, "planet not on screen");
+    (sums[0] / counts[0] as f32, sums[1] / counts[1] as f32)
+}
+
+#[test]
+fn terminator_faces_the_sun() {
+    // Planet to the right of the Sun: the lit side faces left.
+    let (left, right) = lit_halves(Vec3::new(8.0, 0.0, 0.0), Vec3::zeros());
+    assert!(left > 4.0 * right, "left {} right {}", left, right);
+
+    // Planet to the left of the Sun: the lit side faces right.
+    let (left, right) = lit_halves(Vec3::new(-8.0, 0.0, 0.0), Vec3::zeros());
+    assert!(right > 4.0 * left, "left {} right {}", left, right);
+}
+
+#[test]
+fn lighting_follows_the_planet_not_the_origin() {
+    // Moving the planet and the Sun together must not change the shading.
+    let offset = Vec3::new(30.0, -5.0, 12.0);
+    let at_origin = lit_halves(Vec3::new(8.0, 0.0, 0.0), Vec3::zeros());
+    let moved = lit_halves(Vec3::new(8.0, 0.0, 0.0) + offset, offset);
+    assert!((at_origin.0 - moved.0).abs() < 2.0, "{:?} vs {:?}", at_origin, moved);
+    assert!((at_origin.1 - moved.1).abs() < 2.0, "{:?} vs {:?}", at_origin, moved);
+}
@@ -0,0 +1,64 @@
+use nalgebra_glm::Vec3;
+use rust_planets::lod::{projected_radius, LOD_HYSTERESIS};
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{Camera, Framebuffer, LodMeshes, ShaderRegistry, Uniforms};
+
+#[test]
+fn levels_change_past_the_hysteresis_band() {
+    let meshes = LodMeshes::uv_sphere();
+    let thresholds: Vec<f32> = meshes.levels().iter().map(|level| level.min_radius).collect();
+    let top = thresholds.len() - 1;
+
+    assert_eq!(meshes.select(0, 1.0), 0);
+    assert_eq!(meshes.select(0, 10_000.0), top);
+    assert_eq!(meshes.select(top, 1.0), 0);
+
+    // Just past a threshold is not enough to switch either way.
+    let edge = thresholds[1];
+    assert_eq!(meshes.select(0, edge * (1.0 + LOD_HYSTERESIS * 0.5)), 0);
+    assert_eq!(meshes.select(1, edge * (1.0 - LOD_HYSTERESIS * 0.5)), 1);
+    assert_eq!(meshes.select(0, edge * (1.0 + LOD_HYSTERESIS * 1.5)), 1);
+    assert_eq!(meshes.select(1, edge * (1.0 - LOD_HYSTERESIS * 1.5)), 0);
+
+    // Finer levels have more triangles.
+    let counts: Vec<usize> = meshes.levels().iter().map(|level| level.vertices.len()).collect();
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", counts);
+}
+
+#[test]
+fn icosphere_levels_stop_at_the_requested_subdivision() {
+    let meshes = LodMeshes::icosphere(4);
+    let counts: Vec<usize> = meshes.levels().iter().map(|level| level.vertices.len() / 3).collect();
+    assert_eq!(counts, [80, 320, 1280, 5120]);
+
+    let shallow = LodMeshes::icosphere(1);
+    assert_eq!(shallow.levels().len(), 2);
+}
+
+#[test]
+fn distant_bodies_draw_fewer_triangles() {
+    let source = r#"
+        [[body]]
+        name = "Planet"
+        shader = "rocky_planet"
+        scale = 1.0
+    "#;
+    let mut scene = parse_scene(source, LodMeshes::uv_sphere(), &ShaderRegistry::default())
+        .expect("scene should load");
+
+    let (width, height) = (200.0, 150.0);
+    let mut framebuffer = Framebuffer::new(width as usize, height as usize);
+    let mut uniforms = Uniforms::new(width, height);
+    let mut triangles_at = |distance: f32| {
+        let camera = Camera::new(Vec3::new(0.0, 0.0, distance), Vec3::zeros(), Vec3::y());
+        let radius = projected_radius(1.0, distance, &uniforms);
+        let stats = scene.render(&mut framebuffer, &mut uniforms, &camera);
+        assert_eq!(stats.bodies, 1);
+        (radius, stats.triangles)
+    };
+
+    let (near_radius, near) = triangles_at(3.0);
+    let (far_radius, far) = triangles_at(200.0);
+    assert!(near_radius > 50.0 && far_radius < 5.0, "{} {}", near_radius, far_radius);
+    assert!(far > 0 && far * 4 < near, "{} far vs {} near", far, near);
+}
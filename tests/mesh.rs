@@ -0,0 +1,96 @@
+use nalgebra_glm::{Vec2, Vec3};
+use rust_planets::mesh::sphere_uv;
+use rust_planets::shaders::displace;
+use rust_planets::{annulus, icosphere, uv_sphere, Vertex};
+use std::f32::consts::PI;
+
+/// Checks that `vertices` is a unit sphere with radial normals, wound
+/// counter-clockwise from outside, and returns its surface area.
+fn check_sphere(vertices: &[Vertex]) -> f32 {
+    let mut area = 0.0;
+    for triangle in vertices.chunks(3) {
+        for vertex in triangle {
+            assert!((vertex.position.magnitude() - 1.0).abs() < 1e-5);
+            assert!((vertex.normal - vertex.position).magnitude() < 1e-6);
+            assert!((0.0..=1.5).contains(&vertex.tex_coords.x));
+            assert!((0.0..=1.0).contains(&vertex.tex_coords.y));
+        }
+        let [a, b, c] = [triangle[0].position, triangle[1].position, triangle[2].position];
+        let face = (b - a).cross(&(c - a));
+        assert!(face.dot(&(a + b + c)) > 0.0);
+        area += face.magnitude() / 2.0;
+    }
+    area
+}
+
+#[test]
+fn icospheres_are_closed_unit_spheres() {
+    for subdivisions in 0..4 {
+        let vertices = icosphere(subdivisions);
+        assert_eq!(vertices.len(), 3 * 20 * 4usize.pow(subdivisions));
+
+        // Flat faces fall short of the sphere's area, less so as they shrink.
+        let area = check_sphere(&vertices);
+        assert!(area < 4.0 * PI && area > 3.0 * PI, "{} at {}", area, subdivisions);
+    }
+}
+
+#[test]
+fn uv_spheres_match_the_sphere_layout() {
+    let vertices = uv_sphere(16, 32);
+    assert_eq!(vertices.len(), 3 * 960);
+    let area = check_sphere(&vertices);
+    assert!(area < 4.0 * PI && area > 3.8 * PI, "{}", area);
+
+    // Away from the poles and the seam, texture coordinates are exactly
+    // where `sphere_uv` puts the vertex.
+    for vertex in &vertices {
+        let uv = sphere_uv(vertex.position);
+        if vertex.position.y.abs() < 0.99 && (0.01..0.99).contains(&uv.x) {
+            assert!((vertex.tex_coords - uv).magnitude() < 1e-4, "{:?}", vertex.position);
+        }
+    }
+}
+
+#[test]
+fn annulus_spans_its_radii() {
+    let vertices = annulus(1.5, 2.5, 64);
+    assert_eq!(vertices.len(), 6 * 64);
+
+    let mut area = 0.0;
+    for triangle in vertices.chunks(3) {
+        for vertex in triangle {
+            let radius = vertex.position.magnitude();
+            assert!((radius - 1.5).abs() < 1e-5 || (radius - 2.5).abs() < 1e-5);
+            assert_eq!(vertex.position.y, 0.0);
+            assert_eq!(vertex.tex_coords.y, if radius < 2.0 { 0.0 } else { 1.0 });
+            assert_eq!(vertex.normal, Vec3::y());
+        }
+        let [a, b, c] = [triangle[0].position, triangle[1].position, triangle[2].position];
+        let face = (b - a).cross(&(c - a));
+        // Counter-clockwise seen from above.
+        assert!(face.y > 0.0);
+        area += face.magnitude() / 2.0;
+    }
+    let exact = PI * (2.5 * 2.5 - 1.5 * 1.5);
+    assert!(area < exact && area > exact * 0.99, "{}", area);
+}
+
+#[test]
+fn displacement_follows_the_heightfield() {
+    let direction = Vec3::new(1.0, 2.0, 2.0) / 3.0;
+    let vertex = Vertex::new(direction * 2.0, direction, Vec2::zeros());
+
+    let flat = displace(&vertex, 0.0, |_| 1.0);
+    assert_eq!(flat.position, vertex.position);
+
+    // A constant height scales the sphere and leaves normals radial.
+    let raised = displace(&vertex, 0.1, |_| 0.5);
+    assert!((raised.position - direction * 2.1).magnitude() < 1e-5);
+    assert!((raised.normal - direction).magnitude() < 1e-3);
+
+    // Ground rising towards +Y tilts the normal away from +Y.
+    let slope = displace(&vertex, 0.1, |d| d.y);
+    assert!(slope.normal.y < direction.y - 0.01, "{:?}", slope.normal);
+    assert!((slope.normal.magnitude() - 1.0).abs() < 1e-5);
+}
@@ -0,0 +1,142 @@
+use nalgebra_glm::DVec3;
+use rust_planets::nbody::{ConservationMonitor, Integrator, NBodySystem, Particle};
+use std::f64::consts::TAU;
+
+const PLANET_MASS: f64 = 1e-3;
+
+/// A light planet on a circular orbit of radius 1 around a unit mass, with
+/// G = 1, so the orbital period is 2π / sqrt(1 + m).
+fn circular_orbit() -> NBodySystem {
+    let mut system = NBodySystem::new(
+        vec![
+            Particle {
+                mass: 1.0,
+                position: DVec3::zeros(),
+                velocity: DVec3::zeros(),
+            },
+            Particle {
+                mass: PLANET_MASS,
+                position: DVec3::new(1.0, 0.0, 0.0),
+                velocity: DVec3::new(0.0, 0.0, (1.0 + PLANET_MASS).sqrt()),
+            },
+        ],
+        1.0,
+    );
+    system.remove_net_momentum();
+    system
+}
+
+fn run_orbits(integrator: Integrator, orbits: usize, steps_per_orbit: usize) -> NBodySystem {
+    let mut system = circular_orbit();
+    let dt = TAU / (1.0 + PLANET_MASS).sqrt() / steps_per_orbit as f64;
+    for _ in 0..orbits * steps_per_orbit {
+        system.step(dt, integrator);
+    }
+    system
+}
+
+#[test]
+fn symplectic_integrators_keep_energy_bounded() {
+    for integrator in [Integrator::Leapfrog, Integrator::VelocityVerlet] {
+        let initial = circular_orbit();
+        let monitor = ConservationMonitor::new(&initial);
+        let system = run_orbits(integrator, 50, 200);
+        let drift = monitor.drift(&system);
+
+        assert!(
+            drift.relative_energy.abs() < 1e-4,
+            "{:?} drifted by {}",
+            integrator,
+            drift.relative_energy
+        );
+    }
+}
+
+#[test]
+fn all_integrators_conserve_momentum() {
+    for integrator in [Integrator::Leapfrog, Integrator::VelocityVerlet, Integrator::Rk4] {
+        let initial = circular_orbit();
+        let monitor = ConservationMonitor::new(&initial);
+        let system = run_orbits(integrator, 5, 200);
+
+        assert!(monitor.drift(&system).momentum < 1e-12, "{:?}", integrator);
+    }
+}
+
+#[test]
+fn planet_returns_after_one_period() {
+    for integrator in [Integrator::Leapfrog, Integrator::VelocityVerlet, Integrator::Rk4] {
+        let system = run_orbits(integrator, 1, 1000);
+        let planet = system.particles()[1].position - system.particles()[0].position;
+
+        assert!(
+            (planet - DVec3::new(1.0, 0.0, 0.0)).norm() < 1e-3,
+            "{:?} ended at {:?}",
+            integrator,
+            planet
+        );
+    }
+}
+
+#[test]
+fn massless_particles_feel_gravity_without_exerting_it() {
+    let mut system = NBodySystem::new(
+        vec![
+            Particle {
+                mass: 1.0,
+                position: DVec3::zeros(),
+                velocity: DVec3::zeros(),
+            },
+            Particle {
+                mass: 0.0,
+                position: DVec3::new(2.0, 0.0, 0.0),
+                velocity: DVec3::zeros(),
+            },
+        ],
+        1.0,
+    );
+
+    let accelerations = system.accelerations(&[DVec3::zeros(), DVec3::new(2.0, 0.0, 0.0)]);
+    assert_eq!(accelerations[0], DVec3::zeros());
+    assert!((accelerations[1] - DVec3::new(-0.25, 0.0, 0.0)).norm() < 1e-12);
+
+    system.step(0.1, Integrator::Leapfrog);
+    assert_eq!(system.particles()[0].position, DVec3::zeros());
+}
+
+/// Largest `|(E - E0) / E0|` seen over `orbits` orbits.
+fn worst_energy_drift(integrator: Integrator, orbits: usize, steps_per_orbit: usize) -> f64 {
+    let mut system = circular_orbit();
+    let monitor = ConservationMonitor::new(&system);
+    let dt = TAU / (1.0 + PLANET_MASS).sqrt() / steps_per_orbit as f64;
+    let mut worst: f64 = 0.0;
+    for _ in 0..orbits * steps_per_orbit {
+        system.step(dt, integrator);
+        worst = worst.max(monitor.drift(&system).relative_energy.abs());
+    }
+    worst
+}
+
+#[test]
+fn verlet_and_leapfrog_share_an_energy_bound() {
+    for integrator in [Integrator::Leapfrog, Integrator::VelocityVerlet] {
+        let drift = worst_energy_drift(integrator, 20, 100);
+        assert!(drift < 1e-5, "{:?} drifted by {}", integrator, drift);
+    }
+}
+
+#[test]
+fn editing_particles_discards_cached_forces() {
+    let mut system = circular_orbit();
+    system.step(0.01, Integrator::VelocityVerlet);
+    system.particles_mut()[1].position.x += 0.5;
+
+    let mut fresh = NBodySystem::new(system.particles().to_vec(), 1.0);
+    system.step(0.01, Integrator::VelocityVerlet);
+    fresh.step(0.01, Integrator::VelocityVerlet);
+
+    for (edited, fresh) in system.particles().iter().zip(fresh.particles()) {
+        assert_eq!(edited.position, fresh.position);
+        assert_eq!(edited.velocity, fresh.velocity);
+    }
+}
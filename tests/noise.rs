@@ -0,0 +1,67 @@
+use nalgebra_glm::{Vec2, Vec3};
+use rust_planets::color::Color;
+use rust_planets::fragment::Fragment;
+use rust_planets::noise::{fbm, ridged, Fractal};
+use rust_planets::{create_noise, ShaderRegistry, Uniforms};
+
+/// A fragment on the unit sphere at `direction`, carrying texture coordinates
+/// `tex_coords` independently of where it sits.
+fn fragment_at(direction: Vec3, tex_coords: Vec2) -> Fragment {
+    Fragment {
+        position: Vec2::zeros(),
+        color: Color::black(),
+        depth: 0.0,
+        normal: direction,
+        intensity: 1.0,
+        world_position: direction,
+        object_position: direction,
+        tex_coords,
+    }
+}
+
+#[test]
+fn surfaces_have_no_uv_seam() {
+    // Only the fragment stage runs, so the matrices don't matter.
+    let uniforms = Uniforms {
+        camera_position: Vec3::new(3.0, 0.0, 0.0),
+        time: 1.5,
+        ..Uniforms::new(1.0, 1.0)
+    };
+    let sun = Vec3::new(10.0, 2.0, 0.0);
+    let shaders = ShaderRegistry::default();
+
+    let mut params = toml::Table::new();
+    params.insert("class".into(), "ocean".into());
+    params.insert("seed".into(), 7.into());
+
+    for name in shaders.names() {
+        let shader = if name == "procedural" {
+            shaders.create(name, params.clone())
+        } else {
+            shaders.create(name, toml::Table::new())
+        }
+        .expect("built-in shader");
+
+        // Points on the seam, where u wraps from 1 back to 0.
+        for y in [-0.9, -0.4, 0.0, 0.3, 0.8] {
+            let direction = Vec3::new((1.0f32 - y * y).sqrt(), y, 0.0);
+            let v = (y + 1.0) / 2.0;
+            let left = shader.fragment(&fragment_at(direction, Vec2::new(0.0, v)), &uniforms, sun);
+            let right = shader.fragment(&fragment_at(direction, Vec2::new(1.0, v)), &uniforms, sun);
+            assert_eq!(left.to_hex(), right.to_hex(), "{} at y = {}", name, y);
+        }
+    }
+}
+
+#[test]
+fn fractals_stay_in_range() {
+    let noise = create_noise();
+    for i in 0..500 {
+        let t = i as f32 * 0.37;
+        let p = Vec3::new(t.sin() * 7.0, t * 0.21, t.cos() * 5.0);
+        let f = fbm(&noise, p, Fractal::default());
+        let r = ridged(&noise, p, Fractal::default());
+        assert!((-1.0..=1.0).contains(&f), "fbm {} at {:?}", f, p);
+        assert!((0.0..=1.0).contains(&r), "ridged {} at {:?}", r, p);
+    }
+}
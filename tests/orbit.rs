@@ -0,0 +1,77 @@
+use rust_planets::orbit::solve_kepler;
+use rust_planets::OrbitalElements;
+use std::f64::consts::TAU;
+
+fn orbit(eccentricity: f32, inclination: f32) -> OrbitalElements {
+    OrbitalElements {
+        semi_major_axis: 10.0,
+        eccentricity,
+        inclination,
+        longitude_of_ascending_node: 0.3,
+        argument_of_periapsis: 0.7,
+        mean_anomaly_at_epoch: 0.0,
+        period: 40.0,
+    }
+}
+
+#[test]
+fn kepler_solutions_satisfy_the_equation() {
+    for eccentricity in [0.0, 0.1, 0.5, 0.8, 0.9, 0.95, 0.99] {
+        for step in 0..64 {
+            let mean_anomaly = TAU * step as f64 / 64.0;
+            let e = solve_kepler(mean_anomaly, eccentricity);
+            let residual = e - eccentricity * e.sin() - mean_anomaly;
+            assert!(
+                residual.abs() < 1e-9,
+                "e = {}, M = {}: residual {}",
+                eccentricity,
+                mean_anomaly,
+                residual
+            );
+        }
+    }
+}
+
+#[test]
+fn distance_spans_periapsis_to_apoapsis() {
+    for eccentricity in [0.0, 0.3, 0.9] {
+        let elements = orbit(eccentricity, 0.4);
+        let a = elements.semi_major_axis;
+        // Periapsis at the epoch, apoapsis half a period later.
+        let periapsis = elements.position_at(0.0).magnitude();
+        let apoapsis = elements.position_at(elements.period as f64 / 2.0).magnitude();
+        assert!((periapsis - a * (1.0 - eccentricity)).abs() < 1e-4, "{}", periapsis);
+        assert!((apoapsis - a * (1.0 + eccentricity)).abs() < 1e-4, "{}", apoapsis);
+
+        // And never outside that range in between.
+        for step in 0..100 {
+            let distance = elements.position_at(step as f64 * 0.4).magnitude();
+            assert!(distance >= periapsis - 1e-4 && distance <= apoapsis + 1e-4);
+        }
+    }
+}
+
+#[test]
+fn positions_repeat_every_period() {
+    let elements = orbit(0.6, 0.4);
+    let period = elements.period as f64;
+    for time in [0.0, 3.7, 12.5, 31.0] {
+        let first = elements.position_at(time);
+        let later = elements.position_at(time + 5.0 * period);
+        assert!((first - later).magnitude() < 1e-3, "{:?} vs {:?}", first, later);
+    }
+}
+
+#[test]
+fn inclination_tilts_the_orbit_out_of_the_ecliptic() {
+    let flat = orbit(0.2, 0.0);
+    let highest = |elements: &OrbitalElements| {
+        (0..100)
+            .map(|step| elements.position_at(step as f64 * 0.4).y.abs())
+            .fold(0.0, f32::max)
+    };
+    assert!(highest(&flat) < 1e-5);
+
+    let tilted = orbit(0.2, 0.5);
+    assert!(highest(&tilted) > 0.3 * tilted.semi_major_axis);
+}
@@ -0,0 +1,68 @@
+use rust_planets::{PlanetClass, PlanetParams};
+
+#[test]
+fn generation_is_reproducible() {
+    for class in PlanetClass::ALL {
+        assert_eq!(
+            PlanetParams::generate(class, 1234),
+            PlanetParams::generate(class, 1234),
+            "{:?}",
+            class
+        );
+    }
+}
+
+#[test]
+fn seeds_give_distinct_worlds() {
+    for class in PlanetClass::ALL {
+        let worlds: Vec<PlanetParams> =
+            (0..16).map(|seed| PlanetParams::generate(class, seed)).collect();
+        for (i, a) in worlds.iter().enumerate() {
+            for b in &worlds[i + 1..] {
+                assert_ne!(a.palette, b.palette, "{:?} seeds {} and {}", class, a.seed, b.seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn classes_keep_their_character() {
+    for seed in 0..32 {
+        let gas = PlanetParams::generate(PlanetClass::GasGiant, seed);
+        assert!(gas.band_count >= 8.0 && !gas.storms.is_empty());
+
+        let ocean = PlanetParams::generate(PlanetClass::Ocean, seed);
+        let desert = PlanetParams::generate(PlanetClass::Desert, seed);
+        assert!(ocean.sea_level > desert.sea_level);
+        assert!(ocean.cloud_cover > desert.cloud_cover);
+        assert_eq!(desert.band_count, 0.0);
+    }
+}
+
+/// Values recorded when the generator was written. A change here means every
+/// seeded world in existing scene files now looks different.
+#[test]
+fn seeds_give_the_same_worlds_across_versions() {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    let close3 = |a: [f32; 3], b: [f32; 3]| a.iter().zip(&b).all(|(a, b)| close(*a, *b));
+
+    let ocean = PlanetParams::generate(PlanetClass::Ocean, 42);
+    assert!(close3(ocean.palette.sea, [0.035852913, 0.3291034, 0.64342177]), "{:?}", ocean);
+    assert!(close3(ocean.palette.lowland, [0.2900551, 0.6044627, 0.18550327]), "{:?}", ocean);
+    assert!(close3(ocean.palette.peak, [0.93731564, 0.9585231, 0.94899756]), "{:?}", ocean);
+    assert!(close(ocean.zoom, 4.1929264) && close(ocean.sea_level, 0.24116182), "{:?}", ocean);
+    assert!(close(ocean.cloud_cover, 0.5888781), "{:?}", ocean);
+
+    let giant = PlanetParams::generate(PlanetClass::GasGiant, 7);
+    assert_eq!(giant.band_count, 12.0);
+    assert!(close(giant.band_turbulence, 0.39731532), "{:?}", giant);
+    assert_eq!(giant.storms.len(), 3);
+    let storm = &giant.storms[0];
+    assert!(
+        close(storm.longitude, 0.5212498)
+            && close(storm.latitude, 0.6985101)
+            && close(storm.radius, 0.045438424),
+        "{:?}",
+        giant
+    );
+}
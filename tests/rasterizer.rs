@@ -0,0 +1,212 @@
+use nalgebra_glm::{perspective, Vec2, Vec3, Vec4};
+use rust_planets::clipping::project_to_screen;
+use rust_planets::triangle::triangle;
+use rust_planets::shaders::EarthShader;
+use rust_planets::{
+    annulus, create_model_matrix, create_view_matrix, create_viewport_matrix, render, CullMode,
+    Framebuffer, FrontFace, Obj, RasterState, RenderStats, Uniforms, Vertex,
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+const FOVY: f32 = std::f32::consts::FRAC_PI_2;
+
+/// A floor quad at y = -1 seen from the origin looking down -Z, spanning
+/// x in [-1, 1] and z in [-2, -10], with u running along x and v along depth.
+fn floor_vertex(x: f32, z: f32) -> Vertex {
+    let projection = perspective(WIDTH / HEIGHT, FOVY, 0.1, 100.0);
+    let viewport = create_viewport_matrix(WIDTH, HEIGHT);
+
+    let position = Vec3::new(x, -1.0, z);
+    let tex_coords = Vec2::new((x + 1.0) / 2.0, (-z - 2.0) / 8.0);
+    let mut vertex = Vertex::new(position, Vec3::new(0.0, 1.0, 0.0), tex_coords);
+    vertex.clip_position = projection * Vec4::new(x, -1.0, z, 1.0);
+    project_to_screen(&mut vertex, &viewport);
+    vertex
+}
+
+/// Texture coordinates of the floor point seen through the centre of pixel
+/// `(x, y)`, found by intersecting the eye ray with the floor plane.
+fn analytic_tex_coords(x: f32, y: f32) -> Vec2 {
+    let ndc_x = (x + 0.5) / (WIDTH / 2.0) - 1.0;
+    let ndc_y = 1.0 - (y + 0.5) / (HEIGHT / 2.0);
+    let tan_half = (FOVY / 2.0).tan();
+    let direction = Vec3::new(ndc_x * tan_half * WIDTH / HEIGHT, ndc_y * tan_half, -1.0);
+
+    let t = -1.0 / direction.y;
+    let hit = direction * t;
+    Vec2::new((hit.x + 1.0) / 2.0, (-hit.z - 2.0) / 8.0)
+}
+
+#[test]
+fn textured_quad_matches_analytic_reference() {
+    let near_left = floor_vertex(-1.0, -2.0);
+    let near_right = floor_vertex(1.0, -2.0);
+    let far_right = floor_vertex(1.0, -10.0);
+    let far_left = floor_vertex(-1.0, -10.0);
+
+    let mut fragments = triangle(&near_left, &near_right, &far_right);
+    fragments.extend(triangle(&near_left, &far_right, &far_left));
+    assert!(fragments.len() > 1000, "only {} fragments", fragments.len());
+
+    let mut max_error: f32 = 0.0;
+    for fragment in &fragments {
+        let expected = analytic_tex_coords(fragment.position.x, fragment.position.y);
+        max_error = max_error.max((fragment.tex_coords - expected).abs().max());
+    }
+
+    assert!(max_error < 1e-3, "max texture coordinate error {}", max_error);
+}
+
+fn sphere_uniforms(position: Vec3, scale: f32) -> Uniforms {
+    Uniforms {
+        model_matrix: create_model_matrix(position, scale, Vec3::new(0.3, 0.5, 0.0)),
+        view_matrix: create_view_matrix(
+            Vec3::new(0.0, 0.0, 3.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        ),
+        camera_position: Vec3::new(0.0, 0.0, 3.0),
+        time: 1.5,
+        ..Uniforms::new(WIDTH, HEIGHT)
+    }
+}
+
+fn render_sphere(framebuffer: &mut Framebuffer, position: Vec3, scale: f32) -> RenderStats {
+    let sphere = Obj::load("tests/models/sphere.obj").expect("sphere.obj");
+    render(
+        framebuffer,
+        &sphere_uniforms(position, scale),
+        &sphere.get_vertex_array(),
+        &EarthShader::default(),
+        Vec3::new(5.0, 0.0, 5.0),
+        RasterState::default(),
+    )
+}
+
+#[test]
+fn threaded_rendering_matches_single_threaded() {
+    let mut reference = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    reference.set_render_threads(1);
+    render_sphere(&mut reference, Vec3::zeros(), 1.0);
+    assert!(reference.zbuffer.iter().any(|depth| depth.is_finite()));
+
+    for threads in [2, 3, 8] {
+        let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+        framebuffer.set_render_threads(threads);
+        render_sphere(&mut framebuffer, Vec3::zeros(), 1.0);
+        assert!(framebuffer.color == reference.color, "{} threads", threads);
+        assert!(framebuffer.zbuffer == reference.zbuffer, "{} threads", threads);
+    }
+}
+
+#[test]
+fn coarse_depth_rejects_hidden_triangles() {
+    let mut reference = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    render_sphere(&mut reference, Vec3::zeros(), 1.0);
+
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    render_sphere(&mut framebuffer, Vec3::zeros(), 1.0);
+    let hidden = render_sphere(&mut framebuffer, Vec3::new(0.0, 0.0, -6.0), 0.5);
+
+    assert!(hidden.triangles > 0);
+    assert!(hidden.coarse_rejections > 0);
+    assert!(framebuffer.color == reference.color);
+}
+
+/// A triangle facing the camera at the origin, wound counter-clockwise as
+/// the camera sees it unless `clockwise`.
+fn facing_triangle(clockwise: bool) -> Vec<Vertex> {
+    let mut corners = vec![
+        Vec3::new(-1.0, -1.0, -3.0),
+        Vec3::new(1.0, -1.0, -3.0),
+        Vec3::new(0.0, 1.0, -3.0),
+    ];
+    if clockwise {
+        corners.swap(1, 2);
+    }
+    corners
+        .into_iter()
+        .map(|position| Vertex::new(position, Vec3::z(), Vec2::zeros()))
+        .collect()
+}
+
+/// How many pixels a facing triangle covers when drawn with `raster_state`.
+fn drawn_pixels(clockwise: bool, raster_state: RasterState) -> usize {
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    render(
+        &mut framebuffer,
+        &Uniforms::new(WIDTH, HEIGHT),
+        &facing_triangle(clockwise),
+        &EarthShader::default(),
+        Vec3::new(0.0, 0.0, 5.0),
+        raster_state,
+    );
+    framebuffer.zbuffer.iter().filter(|depth| depth.is_finite()).count()
+}
+
+#[test]
+fn cull_modes_discard_the_chosen_winding() {
+    let cases = [
+        (CullMode::Back, FrontFace::CounterClockwise, true, false),
+        (CullMode::Front, FrontFace::CounterClockwise, false, true),
+        (CullMode::None, FrontFace::CounterClockwise, true, true),
+        (CullMode::Back, FrontFace::Clockwise, false, true),
+        (CullMode::Front, FrontFace::Clockwise, true, false),
+        (CullMode::None, FrontFace::Clockwise, true, true),
+    ];
+    let full = drawn_pixels(false, RasterState::double_sided());
+    assert!(full > 100, "only {} pixels", full);
+
+    for (cull_mode, front_face, draws_ccw, draws_cw) in cases {
+        let state = RasterState { cull_mode, front_face };
+        let expected = |draws: bool| if draws { full } else { 0 };
+        assert_eq!(drawn_pixels(false, state), expected(draws_ccw), "ccw, {:?}", state);
+        assert_eq!(drawn_pixels(true, state), expected(draws_cw), "cw, {:?}", state);
+    }
+}
+
+#[test]
+fn default_state_culls_back_faces() {
+    assert_eq!(
+        RasterState::default(),
+        RasterState {
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+        }
+    );
+    assert!(drawn_pixels(false, RasterState::default()) > 0);
+    assert_eq!(drawn_pixels(true, RasterState::default()), 0);
+}
+
+#[test]
+fn rings_are_drawn_from_both_sides() {
+    let ring = annulus(0.5, 1.0, 32);
+    let view_from = |eye: Vec3, raster_state: RasterState| {
+        let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+        let uniforms = Uniforms {
+            view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::z()),
+            camera_position: eye,
+            ..Uniforms::new(WIDTH, HEIGHT)
+        };
+        render(
+            &mut framebuffer,
+            &uniforms,
+            &ring,
+            &EarthShader::default(),
+            Vec3::new(5.0, 0.0, 0.0),
+            raster_state,
+        );
+        framebuffer.zbuffer.iter().filter(|depth| depth.is_finite()).count()
+    };
+
+    let (above, below) = (Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -3.0, 0.0));
+    let double_sided = RasterState::double_sided();
+    let pixels = view_from(above, double_sided);
+    assert!(pixels > 100, "only {} pixels from above", pixels);
+    assert_eq!(view_from(below, double_sided), pixels);
+
+    // Back-face culling would hide it from below.
+    assert_eq!(view_from(above, RasterState::default()), pixels);
+    assert_eq!(view_from(below, RasterState::default()), 0);
+}
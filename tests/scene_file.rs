@@ -0,0 +1,165 @@
+use nalgebra_glm::Vec3;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{LodMeshes, SceneError, ShaderRegistry};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Parses a scene whose first body is a valid star, followed by `body`
+/// (the rest of a `[[body]]` table), and returns the validation error.
+fn body_error(body: &str) -> (usize, String, String) {
+    let source = format!(
+        "[[body]]\nname = \"Sun\"\nshader = \"star\"\nscale = 1.0\n\n[[body]]\n{}",
+        body
+    );
+    match parse_scene(&source, LodMeshes::single(Vec::new()), &ShaderRegistry::default()) {
+        Err(SceneError::Body {
+            index,
+            name,
+            message,
+        }) => (index, name, message),
+        Err(err) => panic!("expected a body error, got {}", err),
+        Ok(_) => panic!("scene should not load:\n{}", source),
+    }
+}
+
+/// Asserts that `body` is rejected as the second entry, named `Bad`, with a
+/// message mentioning `key`.
+fn assert_rejected(body: &str, key: &str) {
+    let (index, name, message) = body_error(body);
+    assert_eq!(index, 1, "{}", message);
+    assert_eq!(name, "Bad", "{}", message);
+    assert!(message.contains(key), "`{}` not in {:?}", key, message);
+}
+
+#[test]
+fn names_must_be_unique_and_not_empty() {
+    let (index, name, message) = body_error("name = \"Sun\"\nshader = \"star\"\nscale = 1.0");
+    assert_eq!((index, name.as_str()), (1, "Sun"));
+    assert!(message.contains("duplicate"), "{}", message);
+
+    let (index, name, message) = body_error("name = \"\"\nshader = \"star\"\nscale = 1.0");
+    assert_eq!((index, name.as_str()), (1, ""));
+    assert!(message.contains("name"), "{}", message);
+}
+
+#[test]
+fn parents_must_be_declared_first() {
+    assert_rejected(
+        "name = \"Bad\"\nparent = \"Moon\"\nshader = \"moon\"\nscale = 1.0",
+        "parent `Moon`",
+    );
+    assert_rejected(
+        "name = \"Bad\"\nparent = \"Bad\"\nshader = \"moon\"\nscale = 1.0",
+        "parent `Bad`",
+    );
+}
+
+#[test]
+fn fixed_and_orbiting_placements_are_exclusive() {
+    assert_rejected(
+        r#"name = "Bad"
+shader = "mars"
+scale = 1.0
+position = [5.0, 0.0, 0.0]
+
+[body.orbit]
+semi_major_axis = 5.0
+period = 10.0
+"#,
+        "`position` and `orbit`",
+    );
+}
+
+#[test]
+fn scale_and_mass_are_checked() {
+    for scale in ["0.0", "-1.0", "nan", "inf"] {
+        assert_rejected(&format!("name = \"Bad\"\nshader = \"mars\"\nscale = {}", scale), "scale");
+    }
+    for mass in ["-1.0", "nan", "inf"] {
+        assert_rejected(
+            &format!("name = \"Bad\"\nshader = \"mars\"\nscale = 1.0\nmass = {}", mass),
+            "mass",
+        );
+    }
+}
+
+#[test]
+fn subdivisions_are_capped() {
+    assert_rejected(
+        "name = \"Bad\"\nshader = \"earth\"\nscale = 1.0\nsubdivisions = 7",
+        "subdivisions",
+    );
+}
+
+#[test]
+fn ring_radii_are_checked() {
+    for (inner, outer) in [(0.5, 2.0), (2.0, 2.0), (2.0, 1.5)] {
+        assert_rejected(
+            &format!(
+                "name = \"Bad\"\nshader = \"saturn\"\nscale = 1.0\n\n[body.ring]\ninner_radius = {:?}\nouter_radius = {:?}",
+                inner, outer
+            ),
+            "ring radii",
+        );
+    }
+    assert_rejected(
+        "name = \"Bad\"\nshader = \"saturn\"\nscale = 1.0\n\n[body.ring]\nscale = 0.0",
+        "ring scale",
+    );
+}
+
+#[test]
+fn placements_and_angles_must_be_finite() {
+    let cases = [
+        ("position = [nan, 0.0, 0.0]", "position"),
+        ("tilt = [0.0, inf, 0.0]", "tilt"),
+        ("\n[body.ring]\ntilt = [-inf, 0.0, 0.0]", "ring tilt"),
+        ("\n[body.orbit]\nsemi_major_axis = 5.0\nperiod = 10.0\ninclination = nan", "inclination"),
+        (
+            "\n[body.orbit]\nsemi_major_axis = 5.0\nperiod = 10.0\nlongitude_of_ascending_node = inf",
+            "longitude_of_ascending_node",
+        ),
+        (
+            "\n[body.orbit]\nsemi_major_axis = 5.0\nperiod = 10.0\nargument_of_periapsis = nan",
+            "argument_of_periapsis",
+        ),
+        (
+            "\n[body.orbit]\nsemi_major_axis = 5.0\nperiod = 10.0\nmean_anomaly_at_epoch = -inf",
+            "mean_anomaly_at_epoch",
+        ),
+    ];
+    for (keys, key) in cases {
+        assert_rejected(
+            &format!("name = \"Bad\"\nshader = \"saturn\"\nscale = 1.0\n{}", keys),
+            &format!("{} must be finite", key),
+        );
+    }
+}
+
+#[test]
+fn the_sun_position_must_be_finite() {
+    let source = "sun_position = [0.0, nan, 0.0]";
+    let result = parse_scene(source, LodMeshes::single(Vec::new()), &ShaderRegistry::default());
+    assert!(matches!(result, Err(SceneError::Sun(message)) if message.contains("sun_position")));
+}
+
+#[test]
+fn tilts_are_given_in_degrees() {
+    let source = r#"
+        [[body]]
+        name = "Saturn"
+        shader = "saturn"
+        scale = 1.0
+        tilt = [90.0, 0.0, -45.0]
+
+        [body.ring]
+        tilt = [0.0, 180.0, 0.0]
+    "#;
+    let scene = parse_scene(source, LodMeshes::single(Vec::new()), &ShaderRegistry::default())
+        .expect("scene should load");
+    let body = &scene.bodies[0];
+    let expected = Vec3::new(FRAC_PI_2, 0.0, -FRAC_PI_4);
+    assert!((body.rotation - expected).norm() < 1e-6, "{:?}", body.rotation);
+
+    let ring = body.ring.as_ref().expect("ring");
+    assert!((ring.rotation - Vec3::new(0.0, PI, 0.0)).norm() < 1e-6, "{:?}", ring.rotation);
+}
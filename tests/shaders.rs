@@ -0,0 +1,62 @@
+use nalgebra_glm::Vec3;
+use rust_planets::color::Color;
+use rust_planets::fragment::Fragment;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{FragmentShader, LodMeshes, ShaderRegistry, Uniforms};
+use serde::Deserialize;
+
+/// A look defined outside the crate, with its own parameters.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Flat {
+    color: [u8; 3],
+}
+
+impl FragmentShader for Flat {
+    fn fragment(&self, _fragment: &Fragment, _uniforms: &Uniforms, _sun: Vec3) -> Color {
+        let [r, g, b] = self.color;
+        Color::new(r, g, b)
+    }
+}
+
+const SCENE: &str = r#"
+[[body]]
+name = "Sun"
+shader = "star"
+scale = 1.0
+
+[[body]]
+name = "Flatland"
+shader = "flat"
+shader_params = { color = [10, 20, 30] }
+position = [5.0, 0.0, 0.0]
+scale = 0.5
+"#;
+
+#[test]
+fn scenes_can_use_registered_shaders() {
+    let mut shaders = ShaderRegistry::default();
+    assert!(parse_scene(SCENE, LodMeshes::single(Vec::new()), &shaders).is_err());
+
+    shaders.register_params::<Flat>("flat");
+    let scene = parse_scene(SCENE, LodMeshes::single(Vec::new()), &shaders).expect("scene should load");
+    assert_eq!(scene.bodies.len(), 2);
+}
+
+#[test]
+fn invalid_shader_params_are_reported() {
+    let shaders = ShaderRegistry::default();
+    let source = r#"
+        [[body]]
+        name = "Jupiter"
+        shader = "jupiter"
+        shader_params = { bandz = 16 }
+        scale = 1.0
+    "#;
+
+    let err = parse_scene(source, LodMeshes::single(Vec::new()), &shaders)
+        .err()
+        .expect("misspelled parameter should fail");
+    let message = err.to_string();
+    assert!(message.contains("Jupiter") && message.contains("bandz"), "{}", message);
+}
@@ -0,0 +1,144 @@
+use nalgebra_glm::Vec3;
+use rust_planets::scene_file::parse_scene;
+use rust_planets::{
+    Camera, Framebuffer, LodMeshes, Occluder, ShaderRegistry, Shadows, Uniforms,
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+
+/// A sun of radius 1 at the origin seen from 100 units along +X, with one
+/// occluder of `radius` halfway there, `offset` units off the line.
+fn halfway(radius: f32, offset: f32) -> f32 {
+    let shadows = Shadows {
+        sun_radius: 1.0,
+        occluders: vec![Occluder {
+            center: Vec3::new(50.0, offset, 0.0),
+            radius,
+        }],
+    };
+    shadows.sun_visibility(Vec3::new(100.0, 0.0, 0.0), Vec3::zeros())
+}
+
+#[test]
+fn visibility_is_the_uncovered_part_of_the_sun() {
+    // Umbra, full sunlight, and an annular eclipse covering a quarter of
+    // the disc.
+    assert_eq!(halfway(1.0, 0.0), 0.0);
+    assert_eq!(halfway(1.0, 5.0), 1.0);
+    assert!((halfway(0.25, 0.0) - 0.75).abs() < 0.01, "{}", halfway(0.25, 0.0));
+
+    // Brightening steadily across the penumbra.
+    let samples: Vec<f32> = (0..=30).map(|i| halfway(1.0, i as f32 * 0.05)).collect();
+    assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", samples);
+    assert!(samples.iter().any(|v| *v > 0.2 && *v < 0.8), "{:?}", samples);
+
+    // A point sun casts hard shadows.
+    let point_sun = |offset: f32| {
+        let shadows = Shadows {
+            sun_radius: 0.0,
+            occluders: vec![Occluder {
+                center: Vec3::new(50.0, offset, 0.0),
+                radius: 1.0,
+            }],
+        };
+        shadows.sun_visibility(Vec3::new(100.0, 0.0, 0.0), Vec3::zeros())
+    };
+    assert_eq!(point_sun(0.99), 0.0);
+    assert_eq!(point_sun(1.01), 1.0);
+}
+
+#[test]
+fn only_bodies_between_a_point_and_the_sun_shadow_it() {
+    let shadows = Shadows {
+        sun_radius: 1.0,
+        occluders: vec![
+            // The star itself, the lit body, and one beyond the Sun.
+            Occluder { center: Vec3::zeros(), radius: 1.0 },
+            Occluder { center: Vec3::new(100.0, 0.0, 0.0), radius: 2.0 },
+            Occluder { center: Vec3::new(-50.0, 0.0, 0.0), radius: 5.0 },
+        ],
+    };
+    let points = [
+        Vec3::new(98.0, 0.0, 0.0),
+        Vec3::new(102.0, 0.0, 0.0),
+        Vec3::new(100.0, 2.0, 0.0),
+    ];
+    for point in points {
+        assert_eq!(shadows.sun_visibility(point, Vec3::zeros()), 1.0, "{:?}", point);
+    }
+}
+
+const SCENE: &str = r#"
+sun_position = [0.0, 0.0, 100.0]
+sun_radius = 2.0
+
+[[body]]
+name = "Planet"
+shader = "rocky_planet"
+scale = 1.0
+
+# Behind the camera, between the planet and the Sun.
+[[body]]
+name = "Moon"
+shader = "rocky_planet"
+position = [0.0, 0.0, 30.0]
+scale = 1.0
+"#;
+
+#[test]
+fn bodies_out_of_view_still_cast_eclipses() {
+    let shaders = ShaderRegistry::default();
+    let mut scene = parse_scene(SCENE, LodMeshes::uv_sphere(), &shaders).expect("scene should load");
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    let mut uniforms = Uniforms::new(WIDTH, HEIGHT);
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 6.0), Vec3::zeros(), Vec3::y());
+    let center = (HEIGHT as usize / 2) * WIDTH as usize + WIDTH as usize / 2;
+
+    let stats = scene.render(&mut framebuffer, &mut uniforms, &camera);
+    assert_eq!((stats.bodies, stats.culled), (1, 1));
+    assert!(framebuffer.zbuffer[center].is_finite());
+    let eclipsed = framebuffer.color[center].luminance();
+
+    scene.bodies[1].visible = false;
+    scene.render(&mut framebuffer, &mut uniforms, &camera);
+    let lit = framebuffer.color[center].luminance();
+    assert!(lit > 0.1 && eclipsed < 0.01 * lit, "{} vs {}", eclipsed, lit);
+
+    let invalid = SCENE.replace("sun_radius = 2.0", "sun_radius = -2.0");
+    let message = parse_scene(&invalid, LodMeshes::uv_sphere(), &shaders)
+        .err()
+        .expect("negative sun radius should fail")
+        .to_string();
+    assert!(message.contains("sun_radius"), "{}", message);
+}
+
+const RINGED: &str = r#"
+sun_position = [100.0, 0.0, 0.0]
+sun_radius = 2.0
+
+[[body]]
+name = "Saturn"
+shader = "saturn"
+scale = 1.0
+
+[body.ring]
+outer_radius = 2.5
+"#;
+
+#[test]
+fn planets_shadow_their_rings() {
+    let shaders = ShaderRegistry::default();
+    let mut scene = parse_scene(RINGED, LodMeshes::uv_sphere(), &shaders).expect("scene should load");
+    let mut framebuffer = Framebuffer::new(WIDTH as usize, HEIGHT as usize);
+    let mut uniforms = Uniforms::new(WIDTH, HEIGHT);
+    let camera = Camera::new(Vec3::new(0.0, 6.0, 6.0), Vec3::zeros(), Vec3::y());
+    scene.render(&mut framebuffer, &mut uniforms, &camera);
+
+    // Ring points on the night side of the planet and beside it.
+    let sun = scene.sun_position;
+    let shadows = &uniforms.shadows;
+    assert_eq!(shadows.sun_visibility(Vec3::new(-2.0, 0.0, 0.0), sun), 0.0);
+    assert_eq!(shadows.sun_visibility(Vec3::new(-2.0, 0.0, 1.2), sun), 1.0);
+    assert_eq!(shadows.sun_visibility(Vec3::new(2.0, 0.0, 0.0), sun), 1.0);
+}
@@ -0,0 +1,84 @@
+use rust_planets::color::Color;
+use rust_planets::{Framebuffer, ToneMapper};
+
+fn gray(value: f32) -> Color {
+    Color::from_float(value, value, value)
+}
+
+#[test]
+fn mappers_are_monotonic_and_displayable() {
+    for mapper in ToneMapper::ALL {
+        assert_eq!(ToneMapper::from_name(mapper.name()), Some(mapper));
+
+        let mut previous = -1.0;
+        for step in 0..=200 {
+            let mapped = mapper.apply(gray(step as f32 * 0.05), 1.0);
+            assert!((0.0..=1.0).contains(&mapped.r), "{} gave {}", mapper.name(), mapped);
+            assert!(mapped.r >= previous, "{} is not monotonic", mapper.name());
+            previous = mapped.r;
+        }
+        assert_eq!(mapper.apply(Color::black(), 1.0), Color::black());
+        assert_eq!(mapper.apply(gray(-2.0), 1.0), Color::black());
+    }
+    assert_eq!(ToneMapper::from_name("filmic"), None);
+}
+
+#[test]
+fn highlights_stay_distinguishable() {
+    // Clamping flattens everything past 1...
+    assert_eq!(ToneMapper::Clamp.apply(gray(1.2), 1.0), ToneMapper::Clamp.apply(gray(2.0), 1.0));
+    assert_eq!(ToneMapper::Clamp.apply(gray(0.5), 1.0), gray(0.5));
+
+    // ...where the curves still roll off.
+    for mapper in [ToneMapper::Reinhard, ToneMapper::Aces, ToneMapper::Exposure] {
+        let (dim, bright) = (mapper.apply(gray(1.2), 1.0), mapper.apply(gray(2.0), 1.0));
+        assert!(bright.to_hex() != dim.to_hex(), "{}", mapper.name());
+    }
+
+    // Exposure scales the input.
+    let mapper = ToneMapper::Reinhard;
+    assert_eq!(mapper.apply(gray(0.5), 2.0), mapper.apply(gray(1.0), 1.0));
+}
+
+#[test]
+fn resolve_writes_the_display_buffer() {
+    let mut framebuffer = Framebuffer::new(4, 2);
+    framebuffer.set_background_color(0x000000);
+    framebuffer.clear();
+    framebuffer.color[3] = gray(3.0);
+    framebuffer.color[5] = Color::from_float(0.5, 0.0, 0.0);
+
+    framebuffer.set_tone_mapper(ToneMapper::Clamp);
+    framebuffer.resolve();
+    assert_eq!(framebuffer.buffer[0], 0x000000);
+    assert_eq!(framebuffer.buffer[3], 0xFFFFFF);
+    // Linear half red is sRGB 188 (0xBC) on screen.
+    assert_eq!(framebuffer.buffer[5], 0xBC0000);
+
+    framebuffer.set_tone_mapper(ToneMapper::Reinhard);
+    framebuffer.resolve();
+    assert_eq!(framebuffer.buffer[3], gray(0.75).to_hex());
+
+    framebuffer.set_exposure(3.0);
+    framebuffer.resolve();
+    assert_eq!(framebuffer.buffer[3], gray(0.9).to_hex());
+}
+
+#[test]
+fn display_colors_are_srgb_encoded() {
+    // The sRGB curve: linear near black, a 2.4 power above.
+    assert_eq!(gray(0.0).to_hex(), 0x000000);
+    assert_eq!(gray(0.001).to_hex(), 0x030303);
+    assert_eq!(gray(0.18).to_hex(), 0x767676);
+    assert_eq!(gray(0.5).to_hex(), 0xBCBCBC);
+    assert_eq!(gray(1.0).to_hex(), 0xFFFFFF);
+
+    // 8-bit inputs are decoded, so they come back unchanged.
+    for level in 0..=255u32 {
+        let hex = (level << 16) | ((255 - level) << 8) | (level / 2);
+        assert_eq!(Color::from_hex(hex).to_hex(), hex, "{:06X}", hex);
+    }
+    let mid = Color::new(128, 128, 128);
+    assert!((mid.r - 0.2158).abs() < 1e-4, "{}", mid);
+    assert!((Color::from_srgb(0.5, 0.5, 0.5).g - 0.2140).abs() < 1e-4);
+}